@@ -8,6 +8,9 @@ pub struct AnimElement {
     pub start: u32,
     pub length: u32,
     pub priority: i32,
+    /// Stays on the last frame instead of moving to the next state
+    #[serde(default)]
+    pub hold: bool,
 }
 
 impl Default for AnimElement {
@@ -16,6 +19,7 @@ impl Default for AnimElement {
             priority: 0,
             start: 0,
             length: 1,
+            hold: false,
         }
     }
 }
@@ -68,9 +72,14 @@ impl Animation {
     pub fn next_frame(&mut self) -> u32 {
         let index = self.index();
         if !self.paused {
-            self.index += 1;
-            if self.index == self.current().length {
-                self.next_state();
+            let current = self.current();
+            if current.hold {
+                self.index = (self.index + 1).min(current.length - 1);
+            } else {
+                self.index += 1;
+                if self.index == current.length {
+                    self.next_state();
+                }
             }
         }
         index
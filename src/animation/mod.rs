@@ -4,12 +4,19 @@ pub use animation_graph::Animation;
 pub use state::{AnimOrientation, AnimState};
 
 use crate::input::*;
+use crate::physic::{FixedStep, TIMESTEP, UPDATE_STAGE};
 use crate::player::Player;
 
 use bevy::prelude::*;
 
 pub const ZOOM: f32 = 6.0;
 
+/// Size in pixels of a tile of the tear sprite sheet
+pub const TEAR_TILE: f32 = 8.0;
+
+/// Radius of a tear of size 1 once zoomed, the tear drawn in its tile leaves a 1 pixel margin
+pub const TEAR_RADIUS: f32 = 0.5 * (TEAR_TILE - 2.0) * ZOOM;
+
 #[derive(Bundle)]
 pub struct AnimationBundle {
     pub animation: Animation,
@@ -49,6 +56,7 @@ impl Default for AnimTimer {
 }
 
 pub struct PlayerAnimEvent {
+    pub player: Entity,
     pub state: AnimState,
 }
 
@@ -56,24 +64,37 @@ pub struct AnimationPlugin;
 
 impl AnimationPlugin {
     fn animation_update(
-        time: Res<Time>,
         mut query: Query<(&mut TextureAtlasSprite, &mut Animation, &mut AnimTimer)>,
     ) {
         for (mut sprite, mut animation, mut timer) in query.iter_mut() {
             if timer.available() {
                 sprite.index = animation.next_frame();
             }
-            timer.tick(time.delta_seconds());
+            timer.tick(TIMESTEP as f32);
         }
     }
 
     fn player_animation(
         mut reader: Local<EventReader<PlayerAnimEvent>>,
-        actions: ChangedRes<Actions<Action>>,
         anim_events: Res<Events<PlayerAnimEvent>>,
-        mut query: Query<(&mut Transform, &mut Animation, &mut AnimTimer), With<Player>>,
+        mut query: Query<
+            (
+                &Actions<Action>,
+                &mut Transform,
+                &mut Animation,
+                &mut AnimTimer,
+            ),
+            With<Player>,
+        >,
     ) {
-        for (mut transform, mut animation, mut timer) in query.iter_mut() {
+        for player_anim in reader.iter(&anim_events) {
+            if let Ok((_, _, mut animation, mut timer)) = query.get_mut(player_anim.player) {
+                animation.set_state(player_anim.state);
+                timer.reset();
+            }
+        }
+
+        for (actions, mut transform, _, _) in query.iter_mut() {
             if let Some(Action::Shoot(direction)) = actions.get(Action::Shoot) {
                 if direction.x > f32::EPSILON {
                     transform.scale.x = ZOOM;
@@ -87,19 +108,14 @@ impl AnimationPlugin {
                     transform.scale.x = -ZOOM;
                 }
             }
-
-            for player_anim in reader.iter(&anim_events) {
-                animation.set_state(player_anim.state);
-                timer.reset();
-            }
         }
     }
 }
 
 impl Plugin for AnimationPlugin {
     fn build(&self, app: &mut AppBuilder) {
-        app.add_event::<PlayerAnimEvent>()
-            .add_system(Self::player_animation.system())
-            .add_system(Self::animation_update.system());
+        app.add_fixed_event::<PlayerAnimEvent>()
+            .add_fixed_system(UPDATE_STAGE, Self::player_animation.system())
+            .add_fixed_system(UPDATE_STAGE, Self::animation_update.system());
     }
 }
@@ -0,0 +1,293 @@
+mod modifier;
+
+use crate::physic::{FixedStep, UPDATE_STAGE};
+use crate::player::*;
+use bevy::prelude::*;
+use std::marker::PhantomData;
+
+pub use modifier::*;
+
+/// A player attribute whose final value is computed by its [Stat](Stat)
+pub trait Attribute: Copy + Send + Sync + 'static {
+    fn value(&self) -> f32;
+    fn from_value(value: f32) -> Self;
+}
+
+macro_rules! float_attribute {
+    ($($attribute:ident),*) => {
+        $(impl Attribute for $attribute {
+            fn value(&self) -> f32 {
+                self.0
+            }
+
+            fn from_value(value: f32) -> Self {
+                Self(value)
+            }
+        })*
+    };
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Health(pub i32);
+
+impl Attribute for Health {
+    fn value(&self) -> f32 {
+        self.0 as f32
+    }
+
+    fn from_value(value: f32) -> Self {
+        Self(value.round() as i32)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Damage(pub f32);
+
+/// Number of shots per second
+///
+/// Its [Stat](Stat) is in tears units, so changes and modifiers apply to the tears stat and the
+/// result goes through [tears_to_rate](tears_to_rate).
+#[derive(Debug, Clone, Copy)]
+pub struct AttackRate(pub f32);
+
+impl AttackRate {
+    /// Delay between two shots, in seconds
+    pub fn cooldown(&self) -> f32 {
+        1.0 / self.0.max(f32::EPSILON)
+    }
+}
+
+/// Distance the tears fly before dropping, when shot from the default height
+#[derive(Debug, Clone, Copy)]
+pub struct Range(pub f32);
+
+impl Range {
+    /// Tears shot from higher take longer to drop, so they fly proportionally farther
+    pub fn distance(&self, height: ShotHeight) -> f32 {
+        self.0 * height.0.max(0.0)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ProjectileSpeed(pub f32);
+
+#[derive(Debug, Clone, Copy)]
+pub struct MovementSpeed(pub f32);
+
+/// Scale of the tears, which also scales their collider
+#[derive(Debug, Clone, Copy)]
+pub struct TearSize(pub f32);
+
+/// Height the tears are shot from, relative to the default height
+#[derive(Debug, Clone, Copy)]
+pub struct ShotHeight(pub f32);
+
+/// Scales the chance of the effects that trigger randomly
+#[derive(Debug, Clone, Copy)]
+pub struct Luck(pub f32);
+
+float_attribute!(
+    Damage,
+    AttackRate,
+    Range,
+    ProjectileSpeed,
+    MovementSpeed,
+    TearSize,
+    ShotHeight,
+    Luck
+);
+
+/// How an [AttributeChange](AttributeChange) modifies the base value of an attribute
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Change {
+    Add(f32),
+    Multiply(f32),
+    Set(f32),
+}
+
+impl Change {
+    pub fn apply(&self, base: f32) -> f32 {
+        match *self {
+            Change::Add(value) => base + value,
+            Change::Multiply(value) => base * value,
+            Change::Set(value) => value,
+        }
+    }
+}
+
+/// Event that inform of a change in a player's base attribute
+///
+/// Every change sent during a frame is applied, in the order they were sent.
+pub struct AttributeChange<A: Attribute> {
+    pub player: Entity,
+    pub change: Change,
+    marker: PhantomData<A>,
+}
+
+impl<A: Attribute> AttributeChange<A> {
+    pub fn new(player: Entity, change: Change) -> Self {
+        Self {
+            player,
+            change,
+            marker: PhantomData,
+        }
+    }
+
+    pub fn set(player: Entity, attribute: A) -> Self {
+        Self::new(player, Change::Set(attribute.value()))
+    }
+
+    pub fn add(player: Entity, value: f32) -> Self {
+        Self::new(player, Change::Add(value))
+    }
+
+    pub fn multiply(player: Entity, value: f32) -> Self {
+        Self::new(player, Change::Multiply(value))
+    }
+}
+
+/// Event sent once the final value of an attribute has actually changed
+pub struct AttributeChanged<A: Attribute> {
+    pub player: Entity,
+    pub old: A,
+    pub new: A,
+}
+
+/// Event stacking a modifier on a player's attribute
+pub struct AddModifier<A: Attribute> {
+    pub player: Entity,
+    pub modifier: Modifier,
+}
+
+pub struct AttributesPlugin;
+
+#[derive(Bundle)]
+pub struct PlayerAttributes {
+    pub health: Health,
+    pub damage: Damage,
+    pub attack_rate: AttackRate,
+    pub range: Range,
+    pub projectile_speed: ProjectileSpeed,
+    pub movement_speed: MovementSpeed,
+    pub tear_size: TearSize,
+    pub shot_height: ShotHeight,
+    pub luck: Luck,
+}
+
+impl PlayerAttributes {
+    /// Stats using the attributes as base values, except the attack rate which uses the tears stat
+    pub fn stats(&self, tears: f32) -> PlayerStats {
+        PlayerStats {
+            health: Stat::new(self.health.value()),
+            damage: Stat::new(self.damage.value()),
+            attack_rate: Stat::new(tears).with_formula(tears_to_rate),
+            range: Stat::new(self.range.value()),
+            projectile_speed: Stat::new(self.projectile_speed.value()),
+            movement_speed: Stat::new(self.movement_speed.value()),
+            tear_size: Stat::new(self.tear_size.value()),
+            shot_height: Stat::new(self.shot_height.value()),
+            luck: Stat::new(self.luck.value()),
+        }
+    }
+}
+
+#[derive(Bundle)]
+pub struct PlayerStats {
+    pub health: Stat<Health>,
+    pub damage: Stat<Damage>,
+    pub attack_rate: Stat<AttackRate>,
+    pub range: Stat<Range>,
+    pub projectile_speed: Stat<ProjectileSpeed>,
+    pub movement_speed: Stat<MovementSpeed>,
+    pub tear_size: Stat<TearSize>,
+    pub shot_height: Stat<ShotHeight>,
+    pub luck: Stat<Luck>,
+}
+
+impl AttributesPlugin {
+    fn update<A: Attribute>(
+        mut event_reader: Local<EventReader<AttributeChange<A>>>,
+        events: Res<Events<AttributeChange<A>>>,
+        mut query: Query<&mut Stat<A>, With<Player>>,
+    ) {
+        for &AttributeChange { player, change, .. } in event_reader.iter(&events) {
+            match query.get_mut(player) {
+                Ok(mut stat) => {
+                    let base = change.apply(stat.base());
+                    stat.set_base(base);
+                }
+                Err(e) => println!("Cannot change attribute for player {:?}: {:?}", player, e),
+            }
+        }
+    }
+
+    fn add_modifier<A: Attribute>(
+        mut event_reader: Local<EventReader<AddModifier<A>>>,
+        events: Res<Events<AddModifier<A>>>,
+        mut query: Query<&mut Stat<A>, With<Player>>,
+    ) {
+        for &AddModifier { player, modifier } in event_reader.iter(&events) {
+            match query.get_mut(player) {
+                Ok(mut stat) => stat.add_modifier(modifier),
+                Err(e) => println!("Cannot add modifier for player {:?}: {:?}", player, e),
+            }
+        }
+    }
+
+    /// Drops the modifiers whose source has been despawned
+    fn prune<A: Attribute>(mut query: Query<&mut Stat<A>>, entities: Query<Entity>) {
+        for mut stat in query.iter_mut() {
+            let gone: Vec<_> = stat
+                .modifiers()
+                .iter()
+                .map(|modifier| modifier.source)
+                .filter(|&source| entities.get(source).is_err())
+                .collect();
+            for source in gone {
+                stat.remove_source(source);
+            }
+        }
+    }
+
+    fn compute<A: Attribute>(
+        mut changed_events: ResMut<Events<AttributeChanged<A>>>,
+        mut query: Query<(Entity, &Stat<A>, &mut A), Changed<Stat<A>>>,
+    ) {
+        for (player, stat, mut attribute) in query.iter_mut() {
+            let new = stat.attribute();
+            if (new.value() - attribute.value()).abs() > f32::EPSILON {
+                changed_events.send(AttributeChanged {
+                    player,
+                    old: *attribute,
+                    new,
+                });
+                *attribute = new;
+            }
+        }
+    }
+
+    fn add_attribute<A: Attribute>(&self, app: &mut AppBuilder) -> &Self {
+        app.add_fixed_event::<AttributeChange<A>>()
+            .add_fixed_event::<AddModifier<A>>()
+            .add_fixed_event::<AttributeChanged<A>>()
+            .add_fixed_system(UPDATE_STAGE, Self::update::<A>.system())
+            .add_fixed_system(UPDATE_STAGE, Self::add_modifier::<A>.system())
+            .add_fixed_system(UPDATE_STAGE, Self::prune::<A>.system())
+            .add_fixed_system(UPDATE_STAGE, Self::compute::<A>.system());
+        self
+    }
+}
+
+impl Plugin for AttributesPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        self.add_attribute::<Health>(app)
+            .add_attribute::<Damage>(app)
+            .add_attribute::<AttackRate>(app)
+            .add_attribute::<Range>(app)
+            .add_attribute::<ProjectileSpeed>(app)
+            .add_attribute::<MovementSpeed>(app)
+            .add_attribute::<TearSize>(app)
+            .add_attribute::<ShotHeight>(app)
+            .add_attribute::<Luck>(app);
+    }
+}
@@ -0,0 +1,175 @@
+use super::Attribute;
+use bevy::prelude::*;
+use std::marker::PhantomData;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ModifierOp {
+    /// Flat bonus added to the base value
+    Add(f32),
+    /// Multiplier applied once every flat bonus has been added
+    Multiply(f32),
+    /// Upper bound of the final value
+    Cap(f32),
+    /// Lower bound of the final value
+    Floor(f32),
+}
+
+/// A single change to an attribute, owned by the entity that caused it
+///
+/// The source can be an item, a status effect, a room... When the source entity is despawned,
+/// the modifier is dropped and the attribute goes back to what it would be without it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Modifier {
+    pub source: Entity,
+    pub op: ModifierOp,
+}
+
+/// Base value of an attribute and the modifiers stacked on top of it
+///
+/// The final value is `formula((base + adds) * multipliers)`, clamped between the highest floor
+/// and the lowest cap. Modifiers are kept sorted by source so the result does not depend on the
+/// order in which they were added.
+#[derive(Debug, Clone)]
+pub struct Stat<A: Attribute> {
+    base: f32,
+    modifiers: Vec<Modifier>,
+    formula: fn(f32) -> f32,
+    marker: PhantomData<A>,
+}
+
+impl<A: Attribute> Stat<A> {
+    pub fn new(base: f32) -> Self {
+        Self {
+            base,
+            modifiers: Vec::new(),
+            formula: |value| value,
+            marker: PhantomData,
+        }
+    }
+
+    /// Converts the modified value before caps and floors are applied
+    pub fn with_formula(mut self, formula: fn(f32) -> f32) -> Self {
+        self.formula = formula;
+        self
+    }
+
+    pub fn base(&self) -> f32 {
+        self.base
+    }
+
+    pub fn set_base(&mut self, base: f32) {
+        self.base = base;
+    }
+
+    pub fn modifiers(&self) -> &[Modifier] {
+        &self.modifiers
+    }
+
+    pub fn add_modifier(&mut self, modifier: Modifier) {
+        let index = self
+            .modifiers
+            .iter()
+            .position(|m| m.source > modifier.source)
+            .unwrap_or(self.modifiers.len());
+        self.modifiers.insert(index, modifier);
+    }
+
+    /// Removes every modifier coming from the given source
+    pub fn remove_source(&mut self, source: Entity) {
+        self.modifiers.retain(|m| m.source != source);
+    }
+
+    pub fn value(&self) -> f32 {
+        let mut add = 0.0;
+        let mut multiply = 1.0;
+        let mut cap = f32::INFINITY;
+        let mut floor = f32::NEG_INFINITY;
+        for modifier in &self.modifiers {
+            match modifier.op {
+                ModifierOp::Add(value) => add += value,
+                ModifierOp::Multiply(value) => multiply *= value,
+                ModifierOp::Cap(value) => cap = cap.min(value),
+                ModifierOp::Floor(value) => floor = floor.max(value),
+            }
+        }
+        (self.formula)((self.base + add) * multiply)
+            .min(cap)
+            .max(floor)
+    }
+
+    pub fn attribute(&self) -> A {
+        A::from_value(self.value())
+    }
+}
+
+/// Isaac's tears stat to shots per second
+///
+/// The game computes a delay in frames (at 30 frames per second) between two shots, which
+/// shrinks with the square root of the tears stat and can go no lower than 5 frames.
+pub fn tears_to_rate(tears: f32) -> f32 {
+    let delay = if tears >= 0.0 {
+        16.0 - 6.0 * (1.3 * tears + 1.0).sqrt()
+    } else if tears > -0.77 {
+        16.0 - 6.0 * (tears + 1.0).sqrt() - 6.0 * tears
+    } else {
+        16.0 - 6.0 * tears
+    };
+    30.0 / (delay.max(5.0) + 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, Copy)]
+    struct Value(f32);
+
+    impl Attribute for Value {
+        fn value(&self) -> f32 {
+            self.0
+        }
+
+        fn from_value(value: f32) -> Self {
+            Self(value)
+        }
+    }
+
+    #[test]
+    fn modifiers_do_not_depend_on_order() {
+        let item = Entity::new(1);
+        let status = Entity::new(2);
+        let mut a = Stat::<Value>::new(3.5);
+        let mut b = Stat::<Value>::new(3.5);
+        let ops = [
+            (status, ModifierOp::Multiply(1.5)),
+            (item, ModifierOp::Add(1.0)),
+            (item, ModifierOp::Cap(6.0)),
+        ];
+        for &(source, op) in ops.iter() {
+            a.add_modifier(Modifier { source, op });
+        }
+        for &(source, op) in ops.iter().rev() {
+            b.add_modifier(Modifier { source, op });
+        }
+        assert_eq!(a.value(), 6.0);
+        assert_eq!(a.value(), b.value());
+
+        a.remove_source(item);
+        assert_eq!(a.value(), 3.5 * 1.5);
+    }
+
+    #[test]
+    fn tears_follow_the_delay_curve() {
+        // Base tears shoot every 10 frames, and the delay can go no lower than 5 frames
+        assert!((tears_to_rate(0.0) - 30.0 / 11.0).abs() < 1e-4);
+        assert_eq!(tears_to_rate(100.0), 5.0);
+        assert!(tears_to_rate(-0.5) < tears_to_rate(0.0));
+
+        let mut stat = Stat::<Value>::new(0.0).with_formula(tears_to_rate);
+        stat.add_modifier(Modifier {
+            source: Entity::new(1),
+            op: ModifierOp::Add(100.0),
+        });
+        assert_eq!(stat.value(), 5.0);
+    }
+}
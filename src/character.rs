@@ -0,0 +1,91 @@
+//! Playable characters, described in `assets/characters/<name>.ron`
+use crate::items::ItemKind;
+use crate::weapons::TearEffect;
+use crate::{arg_value, FromRon};
+use serde::{Deserialize, Serialize};
+
+/// Texture atlas of a character, relative to the assets folder
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CharacterSprite {
+    pub texture: String,
+    pub tile_size: (f32, f32),
+    pub columns: usize,
+    pub rows: usize,
+}
+
+/// Range is the distance in pixels the tears fly before dropping, and tears is Isaac's tears stat
+/// from which the attack rate is derived
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct BaseAttributes {
+    pub damage: f32,
+    pub tears: f32,
+    pub range: f32,
+    pub projectile_speed: f32,
+    pub movement_speed: f32,
+    #[serde(default = "BaseAttributes::one")]
+    pub tear_size: f32,
+    #[serde(default = "BaseAttributes::one")]
+    pub shot_height: f32,
+    #[serde(default)]
+    pub luck: f32,
+}
+
+impl BaseAttributes {
+    fn one() -> f32 {
+        1.0
+    }
+}
+
+/// Hearts the character starts with; soul and black hearts are counted in half hearts
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct StartingHearts {
+    pub containers: u32,
+    #[serde(default)]
+    pub soul: u32,
+    #[serde(default)]
+    pub black: u32,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct CharacterMovement {
+    pub acceleration: f32,
+    pub damping: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Character {
+    pub name: String,
+    pub sprite: CharacterSprite,
+    /// Path of the animation graph
+    pub animation: String,
+    pub attributes: BaseAttributes,
+    pub hearts: StartingHearts,
+    pub movement: CharacterMovement,
+    #[serde(default)]
+    pub items: Vec<ItemKind>,
+    #[serde(default)]
+    pub tear_effects: Vec<TearEffect>,
+}
+
+impl Character {
+    pub fn load(name: &str) -> Result<Self, ron::Error> {
+        Self::from_file(&format!("assets/characters/{}.ron", name))
+    }
+}
+
+/// Name of the character file spawned at the start of the game
+#[derive(Debug, Clone)]
+pub struct SelectedCharacter(pub String);
+
+impl SelectedCharacter {
+    /// Reads the `--character <name>` command line option
+    pub fn from_args() -> Self {
+        arg_value("--character").map(Self).unwrap_or_default()
+    }
+}
+
+impl Default for SelectedCharacter {
+    fn default() -> Self {
+        Self("scorpion".to_string())
+    }
+}
@@ -0,0 +1,262 @@
+use crate::animation::*;
+use crate::attribute::Health;
+use crate::physic::{FixedStep, Impulse, Movement, Position, TIMESTEP, UPDATE_STAGE};
+use crate::FromRon;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeartKind {
+    Soul,
+    Black,
+}
+
+/// Isaac-style hearts, counted in half hearts
+///
+/// Red hearts fill the heart containers. Soul and black hearts are stacked after them and are
+/// always lost first, starting from the last one picked up.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Hearts {
+    containers: u32,
+    red: u32,
+    extra: Vec<HeartKind>,
+}
+
+impl Hearts {
+    /// Full heart containers
+    pub fn new(containers: u32) -> Self {
+        Self {
+            containers,
+            red: containers * 2,
+            extra: Vec::new(),
+        }
+    }
+
+    pub fn containers(&self) -> u32 {
+        self.containers
+    }
+
+    pub fn red(&self) -> u32 {
+        self.red
+    }
+
+    pub fn extra(&self) -> &[HeartKind] {
+        &self.extra
+    }
+
+    pub fn set_containers(&mut self, containers: u32) {
+        self.containers = containers;
+        self.red = self.red.min(containers * 2);
+    }
+
+    pub fn heal(&mut self, halves: u32) {
+        self.red = (self.red + halves).min(self.containers * 2);
+    }
+
+    pub fn add_extra(&mut self, kind: HeartKind, halves: u32) {
+        self.extra
+            .extend(std::iter::repeat(kind).take(halves as usize));
+    }
+
+    /// Removes the given amount of half hearts, returning how many of them were black
+    pub fn damage(&mut self, mut halves: u32) -> u32 {
+        let mut black = 0;
+        while halves > 0 {
+            if let Some(kind) = self.extra.pop() {
+                if kind == HeartKind::Black {
+                    black += 1;
+                }
+            } else if self.red > 0 {
+                self.red -= 1;
+            } else {
+                break;
+            }
+            halves -= 1;
+        }
+        black
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.red == 0 && self.extra.is_empty()
+    }
+}
+
+/// Period after a hit during which damage is ignored
+#[derive(Debug)]
+pub struct Invincibility(pub Timer);
+
+impl Invincibility {
+    pub fn new(duration: f32) -> Self {
+        let mut timer = Timer::from_seconds(duration, false);
+        timer.tick(duration);
+        Self(timer)
+    }
+
+    pub fn is_active(&self) -> bool {
+        !self.0.finished()
+    }
+}
+
+impl Default for Invincibility {
+    fn default() -> Self {
+        Self::new(1.0)
+    }
+}
+
+/// How an entity reacts when it takes damage without dying
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct HitReaction {
+    /// Duration of the invincibility, in seconds
+    pub invincibility: f32,
+    /// Time between two blinks of the sprite while invincible, in seconds
+    pub flash_interval: f32,
+    /// Impulse pushing the entity away from the damage source
+    pub knockback: f32,
+}
+
+impl HitReaction {
+    /// Whether the sprite is hidden at this point of the invincibility
+    pub fn flashing(&self, invincibility: &Invincibility) -> bool {
+        invincibility.is_active()
+            && (invincibility.0.elapsed() / self.flash_interval.max(f32::EPSILON)) as u32 % 2 == 0
+    }
+}
+
+impl Default for HitReaction {
+    fn default() -> Self {
+        Self {
+            invincibility: 1.0,
+            flash_interval: 0.1,
+            knockback: 1500.0,
+        }
+    }
+}
+
+/// Amount is in half hearts, source is the entity dealing the damage if any
+pub struct DamageEvent {
+    pub target: Entity,
+    pub amount: u32,
+    pub source: Option<Entity>,
+}
+
+/// Sent when an entity runs out of hearts
+pub struct Death {
+    pub entity: Entity,
+}
+
+/// Tag component of the entities that died
+pub struct Dead;
+
+pub struct HealthPlugin;
+
+impl HealthPlugin {
+    /// The Health attribute is the maximum red health, in half hearts
+    fn containers(mut query: Query<(&Health, &mut Hearts), Changed<Health>>) {
+        for (health, mut hearts) in query.iter_mut() {
+            hearts.set_containers(health.0.max(0) as u32 / 2);
+        }
+    }
+
+    fn invincibility(mut query: Query<&mut Invincibility>) {
+        for mut invincibility in query.iter_mut() {
+            invincibility.0.tick(TIMESTEP as f32);
+        }
+    }
+
+    fn damage(
+        command: &mut Commands,
+        mut event_reader: Local<EventReader<DamageEvent>>,
+        damage_events: Res<Events<DamageEvent>>,
+        mut death_events: ResMut<Events<Death>>,
+        mut impulses: ResMut<Events<Impulse>>,
+        reaction: Res<HitReaction>,
+        mut query: Query<(&mut Hearts, Option<&mut Invincibility>), Without<Dead>>,
+        mut animations: Query<&mut Animation>,
+        mut movements: Query<&mut Movement>,
+        positions: Query<&Position>,
+    ) {
+        for &DamageEvent {
+            target,
+            amount,
+            source,
+        } in event_reader.iter(&damage_events)
+        {
+            if let Ok((mut hearts, invincibility)) = query.get_mut(target) {
+                // `Dead` is only inserted at the end of the stage, so a second lethal hit in the
+                // same step would kill the entity again
+                if hearts.is_empty() {
+                    continue;
+                }
+                if let Some(mut invincibility) = invincibility {
+                    if invincibility.is_active() {
+                        continue;
+                    }
+                    invincibility.0.set_duration(reaction.invincibility);
+                    invincibility.0.reset();
+                }
+
+                hearts.damage(amount);
+                if !hearts.is_empty() {
+                    if let Ok(mut animation) = animations.get_mut(target) {
+                        animation.set_state(AnimState::Hit(AnimOrientation::Side));
+                    }
+                    let from = source.and_then(|source| positions.get(source).ok());
+                    if let (Some(from), Ok(to)) = (from, positions.get(target)) {
+                        let away = to.current - from.current;
+                        if away.length_squared() > f32::EPSILON {
+                            impulses.send(Impulse {
+                                target,
+                                impulse: away.normalize() * reaction.knockback,
+                            });
+                        }
+                    }
+                } else {
+                    command.insert_one(target, Dead);
+                    death_events.send(Death { entity: target });
+                    if let Ok(mut animation) = animations.get_mut(target) {
+                        animation.set_state(AnimState::Die(AnimOrientation::Side));
+                    }
+                    if let Ok(mut movement) = movements.get_mut(target) {
+                        movement.direction = None;
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Plugin for HealthPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        let reaction = HitReaction::from_file("assets/hit_reaction.ron")
+            .map_err(|e| {
+                println!("{}", e);
+                e
+            })
+            .unwrap_or_default();
+        app.add_resource(reaction)
+            .add_fixed_event::<DamageEvent>()
+            .add_fixed_event::<Death>()
+            .add_fixed_system(UPDATE_STAGE, Self::containers.system())
+            .add_fixed_system(UPDATE_STAGE, Self::invincibility.system())
+            .add_fixed_system(UPDATE_STAGE, Self::damage.system());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extra_hearts_are_lost_before_red_ones() {
+        let mut hearts = Hearts::new(1);
+        hearts.add_extra(HeartKind::Black, 2);
+        hearts.add_extra(HeartKind::Soul, 1);
+
+        assert_eq!(hearts.damage(2), 1);
+        assert_eq!(hearts.red(), 2);
+        assert_eq!(hearts.extra(), &[HeartKind::Black]);
+
+        hearts.damage(4);
+        assert!(hearts.is_empty());
+    }
+}
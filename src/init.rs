@@ -1,89 +1,198 @@
+use crate::animation::*;
+use crate::attribute::*;
+use crate::character::*;
+use crate::health::*;
+use crate::input::{InputDevice, JoinEvent, Unclaimed};
 use crate::physic::*;
 use crate::player::*;
-use crate::render::Materials;
-use crate::weapons::TearWeapon;
+use crate::rng::GameRng;
+use crate::weapons::{TearEffects, TearWeapon};
 use crate::FromRon;
-use crate::{animation::*, items};
 use bevy::prelude::*;
 
 pub struct InitPlugin;
 
 impl InitPlugin {
     const STAGE: &'static str = "game_setup";
+    /// Half size of the walkable floor of the room
+    const ROOM_WIDTH: f32 = 876.0;
+    const ROOM_HEIGHT: f32 = 396.0;
+    const WALL_THICKNESS: f32 = 200.0;
 
-    fn texture_loading(
+    /// Distance between the players when they join
+    const PLAYER_SPACING: f32 = 150.0;
+
+    /// Loads the selected character once, for every player spawned in the game
+    ///
+    /// The game cannot start without its character, so a missing or broken file is fatal.
+    fn load_character(command: &mut Commands, selected: Res<SelectedCharacter>) {
+        let character = Character::load(&selected.0)
+            .unwrap_or_else(|e| panic!("Cannot load character {:?}: {}", selected.0, e));
+        command.insert_resource(character);
+    }
+
+    fn player_spawn(
         command: &mut Commands,
-        asset_server: Res<AssetServer>,
-        mut atlases: ResMut<Assets<TextureAtlas>>,
-        mut textures: ResMut<Assets<ColorMaterial>>,
+        layers: Res<CollisionLayers>,
+        character: Res<Character>,
     ) {
-        asset_server.watch_for_changes().unwrap();
-
-        let player_handle = asset_server.load("scorpion.png");
-        let tear_handle = asset_server.load("tear.png");
-        let ground_handle = asset_server.load("ground.png");
-        let player_atlas = TextureAtlas::from_grid(player_handle, Vec2::new(32.0, 32.0), 5, 5);
-        let tear_atlas = TextureAtlas::from_grid(tear_handle, Vec2::new(8.0, 8.0), 3, 1);
-        command.insert_resource(Materials {
-            player: atlases.add(player_atlas),
-            tears: atlases.add(tear_atlas),
-            ground: textures.add(ground_handle.into()),
-        });
+        Self::spawn_player(
+            command,
+            &layers,
+            &character,
+            InputDevice::Keyboard,
+            Vec2::zero(),
+        );
+        command.with(Unclaimed);
     }
 
-    fn camera_spawn(command: &mut Commands) {
-        command.spawn(Camera2dBundle::default());
+    fn player_join(
+        command: &mut Commands,
+        layers: Res<CollisionLayers>,
+        character: Res<Character>,
+        mut event_reader: Local<EventReader<JoinEvent>>,
+        join_events: Res<Events<JoinEvent>>,
+        mut players: Query<(Entity, &mut InputDevice, Option<&Unclaimed>), With<Player>>,
+    ) {
+        let mut joined: Vec<_> = players.iter_mut().map(|(_, &device, _)| device).collect();
+        let mut unclaimed = players
+            .iter_mut()
+            .find(|(_, _, unclaimed)| unclaimed.is_some())
+            .map(|(player, _, _)| player);
+        for &JoinEvent { device } in event_reader.iter(&join_events) {
+            if joined.contains(&device) {
+                continue;
+            }
+            if let Some(player) = unclaimed.take() {
+                // Nobody played yet, the device takes over the first player
+                if let Ok((_, mut player_device, _)) = players.get_mut(player) {
+                    joined.retain(|&joined| joined != *player_device);
+                    *player_device = device;
+                }
+                command.remove_one::<Unclaimed>(player);
+            } else {
+                let position = Vec2::new(Self::PLAYER_SPACING * joined.len() as f32, 0.0);
+                Self::spawn_player(command, &layers, &character, device, position);
+            }
+            joined.push(device);
+        }
     }
 
-    fn player_spawn(command: &mut Commands, materials: Res<Materials>) {
-        let animation = Animation::from_file("assets/scorpion.ron")
+    fn spawn_player(
+        command: &mut Commands,
+        layers: &CollisionLayers,
+        character: &Character,
+        device: InputDevice,
+        position: Vec2,
+    ) {
+        let animation = Animation::from_file(&character.animation)
             .map_err(|e| {
                 println!("{:?}", e.to_string());
                 e
             })
             .unwrap_or_default();
+        let base = character.attributes;
+        let attributes = PlayerAttributes {
+            health: Health(2 * character.hearts.containers as i32),
+            damage: Damage(base.damage),
+            attack_rate: AttackRate(tears_to_rate(base.tears)),
+            range: Range(base.range),
+            projectile_speed: ProjectileSpeed(base.projectile_speed),
+            movement_speed: MovementSpeed(base.movement_speed),
+            tear_size: TearSize(base.tear_size),
+            shot_height: ShotHeight(base.shot_height),
+            luck: Luck(base.luck),
+        };
+        let mut hearts = Hearts::new(character.hearts.containers);
+        hearts.add_extra(HeartKind::Soul, character.hearts.soul);
+        hearts.add_extra(HeartKind::Black, character.hearts.black);
+
         command
             .spawn(PlayerBundle {
                 player: Player,
-                weapon: TearWeapon::new(0.5, 700.0, 3.0),
+                weapon: TearWeapon::new(
+                    attributes.attack_rate.cooldown(),
+                    attributes.projectile_speed.0,
+                    attributes.range.distance(attributes.shot_height),
+                    attributes.tear_size.0,
+                ),
+                position: Position::new(position),
                 velocity: Default::default(),
+                knockback: Default::default(),
                 movement: Movement {
                     direction: None,
-                    acceleration: 5000.0,
-                    speed: 500.0,
-                    damping: 1500.0,
+                    acceleration: character.movement.acceleration,
+                    speed: attributes.movement_speed.0,
+                    damping: character.movement.damping,
                 },
+                collider: Collider::circle(48.0).with_groups(layers.profile("player")),
+                actions: Default::default(),
+                device,
             })
-            .with_bundle(SpriteSheetBundle {
-                texture_atlas: materials.player.clone(),
-                transform: Transform::from_scale(Vec3::splat(ZOOM)),
+            .with(hearts)
+            .with(Invincibility::default())
+            .with(TearEffects(character.tear_effects.clone()))
+            .with_bundle(attributes.stats(base.tears))
+            .with_bundle(attributes)
+            .with(character.sprite.clone())
+            .with(Transform {
+                translation: position.extend(0.0),
+                scale: Vec3::splat(ZOOM),
                 ..Default::default()
             })
             .with_bundle(AnimationBundle {
                 anim_timer: AnimTimer::new(10.0),
                 animation,
-            })
-            .with(items::TinyPlanet);
+            });
+        for item in &character.items {
+            item.give(command);
+        }
     }
 
-    fn ground_spawn(command: &mut Commands, materials: Res<Materials>) {
-        command.spawn(SpriteBundle {
-            transform: Transform::from_scale(Vec3::splat(ZOOM)),
-            material: materials.ground.clone(),
-            ..Default::default()
-        });
+    fn walls_spawn(command: &mut Commands, layers: Res<CollisionLayers>) {
+        let groups = layers.profile("obstacle");
+        let (width, height) = (Self::ROOM_WIDTH, Self::ROOM_HEIGHT);
+        let half_thickness = 0.5 * Self::WALL_THICKNESS;
+        let walls = [
+            (
+                Vec2::new(-width - half_thickness, 0.0),
+                Vec2::new(half_thickness, height),
+            ),
+            (
+                Vec2::new(width + half_thickness, 0.0),
+                Vec2::new(half_thickness, height),
+            ),
+            (
+                Vec2::new(0.0, -height - half_thickness),
+                Vec2::new(width, half_thickness),
+            ),
+            (
+                Vec2::new(0.0, height + half_thickness),
+                Vec2::new(width, half_thickness),
+            ),
+        ];
+
+        for &(center, half_extents) in walls.iter() {
+            command.spawn((
+                Position::new(center),
+                Collider::aabb(half_extents).with_groups(groups),
+                Solid,
+            ));
+        }
     }
 }
 
 impl Plugin for InitPlugin {
     fn build(&self, app: &mut AppBuilder) {
-        app.add_startup_system(Self::camera_spawn.system())
-            .add_startup_system(Self::texture_loading.system())
+        app.add_resource(SelectedCharacter::from_args())
+            .add_resource(GameRng::from_args())
+            .add_startup_system(Self::load_character.system())
             .add_startup_stage(
                 Self::STAGE,
                 SystemStage::parallel()
-                    .with_system(Self::ground_spawn.system())
+                    .with_system(Self::walls_spawn.system())
                     .with_system(Self::player_spawn.system()),
-            );
+            )
+            .add_fixed_system(UPDATE_STAGE, Self::player_join.system());
     }
 }
@@ -0,0 +1,707 @@
+#![allow(clippy::mem_discriminant_non_enum)]
+mod script;
+
+use crate::physic::{self, FixedStep, Position};
+use crate::player::Player;
+use crate::{arg_value, FromRon};
+
+use bevy::prelude::*;
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::mem::{discriminant, Discriminant};
+
+pub use script::{Script, ScriptStep, ScriptedInput};
+
+pub struct Actions<E> {
+    /// Currently active player actions
+    active: HashMap<Discriminant<E>, E>,
+    /// New actions of the player this step
+    new: HashMap<Discriminant<E>, E>,
+    /// Action finished this step
+    finished: HashMap<Discriminant<E>, E>,
+    /// Inputs currently held down, in the order they were pressed
+    held: Vec<E>,
+}
+
+impl<E> Actions<E>
+where
+    E: Copy,
+{
+    pub fn update(&mut self) {
+        self.new.clear();
+        self.finished.clear();
+    }
+
+    pub fn start(&mut self, action: E) {
+        let key = discriminant(&action);
+        if !self.active.contains_key(&key) {
+            self.new.insert(key, action);
+        }
+
+        self.active.insert(key, action);
+    }
+
+    pub fn stop(&mut self, action: E) {
+        let key = discriminant(&action);
+        if let Some(action) = self.active.remove(&key) {
+            self.finished.insert(key, action);
+        }
+    }
+
+    pub fn get<T: Default>(&self, action: fn(T) -> E) -> Option<&E> {
+        self.active.get(&discriminant(&action(Default::default())))
+    }
+
+    pub fn just_started<T: Default>(&self, action: fn(T) -> E) -> Option<&E> {
+        self.new.get(&discriminant(&action(Default::default())))
+    }
+
+    /// Whether an action without payload, like a button press, started this step
+    pub fn just_triggered(&self, action: E) -> bool {
+        self.new.contains_key(&discriminant(&action))
+    }
+
+    pub fn just_finished<T: Default>(&self, action: fn(T) -> E) -> Option<&E> {
+        self.finished
+            .get(&discriminant(&action(Default::default())))
+    }
+
+    /// Every active action
+    pub fn active(&self) -> impl Iterator<Item = &E> {
+        self.active.values()
+    }
+
+    /// Held inputs of the given kind, oldest first
+    pub fn held<T: Default>(&self, action: fn(T) -> E) -> impl Iterator<Item = &E> {
+        let key = discriminant(&action(Default::default()));
+        self.held
+            .iter()
+            .filter(move |held| discriminant(*held) == key)
+    }
+
+    /// Most recently pressed input of the given kind that is still held
+    pub fn last_pressed<T: Default>(&self, action: fn(T) -> E) -> Option<&E> {
+        self.held(action).last()
+    }
+}
+
+impl<E> Actions<E>
+where
+    E: Copy + PartialEq,
+{
+    /// Updates the held inputs, keeping the press order of the ones that were already held
+    pub fn hold(&mut self, inputs: &[E]) {
+        self.held.retain(|held| inputs.contains(held));
+        for input in inputs {
+            if !self.held.contains(input) {
+                self.held.push(*input);
+            }
+        }
+    }
+}
+
+impl<E> Default for Actions<E> {
+    fn default() -> Self {
+        Self {
+            active: Default::default(),
+            new: Default::default(),
+            finished: Default::default(),
+            held: Default::default(),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Action {
+    Move(Vec2),
+    Shoot(Vec2),
+    Item,
+    Bomb,
+    Card,
+    // Drop,
+    Debug,
+}
+
+impl Action {
+    /// Every action that can be bound to a key
+    pub fn bindable() -> [Action; 12] {
+        use Action::*;
+        [
+            Move(Vec2::new(0.0, 1.0)),
+            Move(Vec2::new(-1.0, 0.0)),
+            Move(Vec2::new(0.0, -1.0)),
+            Move(Vec2::new(1.0, 0.0)),
+            Shoot(Vec2::new(0.0, 1.0)),
+            Shoot(Vec2::new(-1.0, 0.0)),
+            Shoot(Vec2::new(0.0, -1.0)),
+            Shoot(Vec2::new(1.0, 0.0)),
+            Item,
+            Bomb,
+            Card,
+            Debug,
+        ]
+    }
+}
+
+/// Analog stick of a gamepad
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+pub enum Stick {
+    Left,
+    Right,
+}
+
+impl Stick {
+    fn axes(&self) -> (GamepadAxisType, GamepadAxisType) {
+        match self {
+            Stick::Left => (GamepadAxisType::LeftStickX, GamepadAxisType::LeftStickY),
+            Stick::Right => (GamepadAxisType::RightStickX, GamepadAxisType::RightStickY),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct GamepadBindings {
+    movement: Stick,
+    shoot: Stick,
+    /// Sticks are ignored below this length, and rescaled above it
+    deadzone: f32,
+    buttons: HashMap<GamepadButtonType, Action>,
+}
+
+impl GamepadBindings {
+    /// Moves the stick value back to the `[0, 1]` range out of the deadzone
+    fn apply_deadzone(&self, value: Vec2) -> Vec2 {
+        let length = value.length();
+        if length <= self.deadzone {
+            Vec2::zero()
+        } else {
+            let scaled = (length - self.deadzone) / (1.0 - self.deadzone).max(f32::EPSILON);
+            value / length * scaled.min(1.0)
+        }
+    }
+}
+
+impl Default for GamepadBindings {
+    fn default() -> Self {
+        use Action::*;
+        Self {
+            movement: Stick::Left,
+            shoot: Stick::Right,
+            deadzone: 0.2,
+            buttons: vec![
+                (GamepadButtonType::North, Shoot(Vec2::new(0.0, 1.0))),
+                (GamepadButtonType::West, Shoot(Vec2::new(-1.0, 0.0))),
+                (GamepadButtonType::South, Shoot(Vec2::new(0.0, -1.0))),
+                (GamepadButtonType::East, Shoot(Vec2::new(1.0, 0.0))),
+                (GamepadButtonType::RightTrigger, Item),
+                (GamepadButtonType::LeftTrigger, Bomb),
+                (GamepadButtonType::LeftTrigger2, Card),
+                (GamepadButtonType::Select, Debug),
+            ]
+            .into_iter()
+            .collect(),
+        }
+    }
+}
+
+/// How the directions of several held inputs are combined
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DirectionPolicy {
+    /// Directions add up, so opposite ones cancel out
+    Sum,
+    /// Only the most recently pressed direction counts, like in the original game
+    LastPressed,
+}
+
+impl DirectionPolicy {
+    fn resolve<'a>(&self, directions: impl Iterator<Item = &'a Action>) -> Vec2 {
+        let direction = |action: &Action| match *action {
+            Action::Move(direction) | Action::Shoot(direction) => direction,
+            _ => Vec2::zero(),
+        };
+        match self {
+            DirectionPolicy::Sum => directions.map(direction).fold(Vec2::zero(), |a, b| a + b),
+            DirectionPolicy::LastPressed => directions.last().map(direction).unwrap_or_default(),
+        }
+    }
+}
+
+/// A key is already bound to another action
+#[derive(Debug, Clone, Copy)]
+pub struct BindingConflict {
+    pub key: KeyCode,
+    pub action: Action,
+}
+
+/// Content of `key_bindings.ron`
+///
+/// Several keys can trigger the same action, but a key triggers a single action.
+#[derive(Serialize, Deserialize)]
+pub struct KeyBindings {
+    keyboard: HashMap<KeyCode, Action>,
+    #[serde(default)]
+    gamepad: GamepadBindings,
+    /// Holding this button shoots towards the mouse cursor
+    #[serde(default)]
+    mouse_aim: Option<MouseButton>,
+    #[serde(default = "KeyBindings::sum")]
+    move_policy: DirectionPolicy,
+    #[serde(default = "KeyBindings::last_pressed")]
+    shoot_policy: DirectionPolicy,
+}
+
+/// Former content of `key_bindings.ron`, which only bound keyboard keys
+#[derive(Deserialize)]
+struct LegacyKeyBindings(HashMap<KeyCode, Action>);
+
+impl KeyBindings {
+    pub const PATH: &'static str = "key_bindings.ron";
+
+    /// Reads the bindings, migrating files written in the former format
+    pub fn load(path: &str) -> Result<Self, ron::Error> {
+        let ron = std::fs::read_to_string(path)?;
+        ron::from_str(&ron).or_else(|e| {
+            let LegacyKeyBindings(keyboard) = ron::from_str(&ron).map_err(|_| e)?;
+            debug!("Migrating {} from the keyboard only format", path);
+            Ok(Self {
+                keyboard,
+                ..Self::wasd()
+            })
+        })
+    }
+
+    fn sum() -> DirectionPolicy {
+        DirectionPolicy::Sum
+    }
+
+    fn last_pressed() -> DirectionPolicy {
+        DirectionPolicy::LastPressed
+    }
+
+    fn get(&self, key: &KeyCode) -> Option<Action> {
+        self.keyboard.get(key).copied()
+    }
+
+    pub fn keys(&self, action: Action) -> Vec<KeyCode> {
+        let mut keys: Vec<_> = self
+            .keyboard
+            .iter()
+            .filter(|(_, &bound)| bound == action)
+            .map(|(&key, _)| key)
+            .collect();
+        keys.sort();
+        keys
+    }
+
+    /// Adds a key to the action, unless the key is already used by another action
+    pub fn bind(&mut self, key: KeyCode, action: Action) -> Result<(), BindingConflict> {
+        match self.get(&key) {
+            Some(bound) if bound != action => Err(BindingConflict { key, action: bound }),
+            _ => {
+                self.keyboard.insert(key, action);
+                Ok(())
+            }
+        }
+    }
+
+    /// Adds a key to the action, removing it from the action it was bound to
+    pub fn rebind(&mut self, key: KeyCode, action: Action) {
+        self.keyboard.insert(key, action);
+    }
+
+    pub fn clear(&mut self, action: Action) {
+        self.keyboard.retain(|_, &mut bound| bound != action);
+    }
+
+    /// Actions that no key can trigger
+    pub fn unbound(&self) -> Vec<Action> {
+        Action::bindable()
+            .iter()
+            .copied()
+            .filter(|&action| self.keys(action).is_empty())
+            .collect()
+    }
+
+    /// Writes the bindings, unless the file already holds bindings that cannot be read, which
+    /// would be lost
+    pub fn save(&self, path: &str) -> Result<(), ron::Error> {
+        if std::path::Path::new(path).exists() {
+            Self::load(path)?;
+        }
+        let ron = ron::ser::to_string_pretty(self, Default::default())?;
+        std::fs::write(path, ron)?;
+        Ok(())
+    }
+
+    fn wasd() -> Self {
+        use Action::*;
+        Self {
+            keyboard: vec![
+                (KeyCode::W, Move(Vec2::new(0.0, 1.0))),
+                (KeyCode::A, Move(Vec2::new(-1.0, 0.0))),
+                (KeyCode::S, Move(Vec2::new(0.0, -1.0))),
+                (KeyCode::D, Move(Vec2::new(1.0, 0.0))),
+                (KeyCode::Up, Shoot(Vec2::new(0.0, 1.0))),
+                (KeyCode::Left, Shoot(Vec2::new(-1.0, 0.0))),
+                (KeyCode::Down, Shoot(Vec2::new(0.0, -1.0))),
+                (KeyCode::Right, Shoot(Vec2::new(1.0, 0.0))),
+                (KeyCode::F1, Debug),
+            ]
+            .into_iter()
+            .collect(),
+            gamepad: Default::default(),
+            mouse_aim: None,
+            move_policy: Self::sum(),
+            shoot_policy: Self::last_pressed(),
+        }
+    }
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self::wasd()
+    }
+}
+
+/// Position of the mouse cursor in the world, if there is a window to point at
+#[derive(Debug, Default)]
+pub struct Cursor(pub Option<Vec2>);
+
+/// Whether the keyboard currently controls the game, or a menu like the rebinding screen
+pub struct InputFocus {
+    pub gameplay: bool,
+}
+
+impl Default for InputFocus {
+    fn default() -> Self {
+        Self { gameplay: true }
+    }
+}
+
+/// Where the actions of the players come from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputSource {
+    /// Keyboard, mouse and gamepads
+    Devices,
+    /// Actions are written by another plugin, like a replay
+    External,
+}
+
+impl Default for InputSource {
+    fn default() -> Self {
+        InputSource::Devices
+    }
+}
+
+/// Device controlling a player
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InputDevice {
+    /// Keyboard, along with the mouse
+    Keyboard,
+    Gamepad(Gamepad),
+}
+
+/// Sent when a device that does not control any player asks to join the game
+pub struct JoinEvent {
+    pub device: InputDevice,
+}
+
+/// Tag of the first player until it is played, any device used before then takes it over
+pub struct Unclaimed;
+
+/// Inputs of a device for the current frame, before they are turned into actions
+#[derive(Debug, Default)]
+struct InputFrame {
+    /// Analog movement of the sticks
+    movement: Vec2,
+    /// Analog shooting direction of the sticks
+    shoot: Vec2,
+    /// Free aiming direction, which is not snapped to the axes like `shoot`
+    aim: Option<Vec2>,
+    /// Actions of the keys and buttons held down
+    pressed: Vec<Action>,
+    /// Actions pressed by a script for the current step
+    scripted: Vec<Action>,
+    /// The join button was pressed since the last step
+    join: bool,
+}
+
+impl InputFrame {
+    fn is_idle(&self) -> bool {
+        self.movement == Vec2::zero()
+            && self.shoot == Vec2::zero()
+            && self.aim.is_none()
+            && self.pressed.is_empty()
+            && self.scripted.is_empty()
+            && !self.join
+    }
+
+    fn press(&mut self, action: Action) {
+        if !self.pressed.contains(&action) {
+            self.pressed.push(action);
+        }
+    }
+}
+
+/// Frames of every connected device
+///
+/// Frames are read by the fixed steps, so they keep the inputs of the frames without any step
+/// until one consumes them.
+#[derive(Default)]
+struct InputFrames {
+    devices: HashMap<InputDevice, InputFrame>,
+    consumed: bool,
+}
+
+pub struct InputPlugin;
+
+impl InputPlugin {
+    pub const STAGE: &'static str = "isaac_input";
+    const BUTTONS: [Action; 4] = [Action::Item, Action::Bomb, Action::Card, Action::Debug];
+    const JOIN: GamepadButtonType = GamepadButtonType::Start;
+
+    fn begin_frame(mut frames: ResMut<InputFrames>) {
+        if frames.consumed {
+            frames.devices.clear();
+            frames.consumed = false;
+        }
+    }
+
+    fn keyboard(
+        mut frames: ResMut<InputFrames>,
+        source: Res<InputSource>,
+        focus: Res<InputFocus>,
+        keys: Res<Input<KeyCode>>,
+        bindings: Res<KeyBindings>,
+    ) {
+        if *source != InputSource::Devices {
+            return;
+        }
+        let frame = frames.devices.entry(InputDevice::Keyboard).or_default();
+        if !focus.gameplay {
+            return;
+        }
+        keys.get_pressed()
+            .filter_map(|key| bindings.get(key))
+            .for_each(|action| frame.press(action));
+    }
+
+    fn gamepad(
+        mut gamepads: Local<HashSet<Gamepad>>,
+        mut event_reader: Local<EventReader<GamepadEvent>>,
+        gamepad_events: Res<Events<GamepadEvent>>,
+        mut frames: ResMut<InputFrames>,
+        source: Res<InputSource>,
+        axes: Res<Axis<GamepadAxis>>,
+        buttons: Res<Input<GamepadButton>>,
+        bindings: Res<KeyBindings>,
+    ) {
+        for GamepadEvent(gamepad, event) in event_reader.iter(&gamepad_events) {
+            match event {
+                GamepadEventType::Connected => {
+                    debug!("Connected {:?}", gamepad);
+                    gamepads.insert(*gamepad);
+                }
+                GamepadEventType::Disconnected => {
+                    debug!("Disconnected {:?}", gamepad);
+                    gamepads.remove(gamepad);
+                }
+                _ => (),
+            }
+        }
+
+        if *source != InputSource::Devices {
+            return;
+        }
+
+        let bindings = &bindings.gamepad;
+        let stick = |gamepad: Gamepad, stick: Stick| {
+            let (x, y) = stick.axes();
+            let x = axes.get(GamepadAxis(gamepad, x)).unwrap_or_default();
+            let y = axes.get(GamepadAxis(gamepad, y)).unwrap_or_default();
+            bindings.apply_deadzone(Vec2::new(x, y))
+        };
+
+        for &gamepad in gamepads.iter() {
+            let frame = frames
+                .devices
+                .entry(InputDevice::Gamepad(gamepad))
+                .or_default();
+            frame.movement = stick(gamepad, bindings.movement);
+            frame.shoot = stick(gamepad, bindings.shoot);
+            frame.join |= buttons.just_pressed(GamepadButton(gamepad, Self::JOIN));
+            for (&button, &action) in bindings.buttons.iter() {
+                if buttons.pressed(GamepadButton(gamepad, button)) {
+                    frame.press(action);
+                }
+            }
+        }
+    }
+
+    fn mouse(
+        mut frames: ResMut<InputFrames>,
+        buttons: Res<Input<MouseButton>>,
+        cursor: Res<Cursor>,
+        bindings: Res<KeyBindings>,
+        players: Query<(&Position, &InputDevice), With<Player>>,
+    ) {
+        let pressed = bindings
+            .mouse_aim
+            .map_or(false, |button| buttons.pressed(button));
+        if let (true, Some(cursor)) = (pressed, cursor.0) {
+            for (position, &device) in players.iter() {
+                let aim = cursor - position.current;
+                if device == InputDevice::Keyboard && aim.length_squared() > f32::EPSILON {
+                    frames.devices.entry(device).or_default().aim = Some(aim.normalize());
+                }
+            }
+        }
+    }
+
+    fn script(mut frames: ResMut<InputFrames>, mut scripted: ResMut<ScriptedInput>) {
+        scripted.run(&mut frames);
+    }
+
+    /// Turns the frame inputs of each player's device into their actions, once per fixed step
+    fn resolve(
+        mut frames: ResMut<InputFrames>,
+        source: Res<InputSource>,
+        bindings: Res<KeyBindings>,
+        mut query: Query<(&InputDevice, &mut Actions<Action>)>,
+    ) {
+        frames.consumed = true;
+        if *source != InputSource::Devices {
+            return;
+        }
+        let idle = InputFrame::default();
+        for (device, mut actions) in query.iter_mut() {
+            let frame = frames.devices.get(device).unwrap_or(&idle);
+            Self::resolve_frame(frame, &bindings, &mut actions);
+        }
+    }
+
+    fn resolve_frame(frame: &InputFrame, bindings: &KeyBindings, actions: &mut Actions<Action>) {
+        actions.update();
+        let pressed: Vec<_> = frame
+            .pressed
+            .iter()
+            .chain(&frame.scripted)
+            .copied()
+            .collect();
+        actions.hold(&pressed);
+
+        // Keyboard directions add up to more than one, analog sticks keep their magnitude
+        let movement = frame.movement + bindings.move_policy.resolve(actions.held(Action::Move));
+        if movement.length_squared() > f32::EPSILON {
+            let length = movement.length();
+            actions.start(Action::Move(movement / length.max(1.0)));
+        } else {
+            actions.stop(Action::Move(Default::default()));
+        }
+
+        // Like in Isaac, tears go along the dominant axis, horizontal shots winning ties
+        let shoot = frame.shoot + bindings.shoot_policy.resolve(actions.held(Action::Shoot));
+        let shoot = if shoot.length_squared() <= f32::EPSILON {
+            Vec2::zero()
+        } else if shoot.x.abs() >= shoot.y.abs() {
+            Vec2::new(shoot.x.signum(), 0.0)
+        } else {
+            Vec2::new(0.0, shoot.y.signum())
+        };
+        if let Some(aim) = frame.aim {
+            actions.start(Action::Shoot(aim));
+        } else if shoot != Vec2::zero() {
+            actions.start(Action::Shoot(shoot));
+        } else {
+            actions.stop(Action::Shoot(Default::default()));
+        }
+
+        for &action in Self::BUTTONS.iter() {
+            if pressed.contains(&action) {
+                actions.start(action);
+                if actions.just_triggered(action) {
+                    debug!("Pressed {:?}", action);
+                }
+            } else {
+                actions.stop(action);
+            }
+        }
+    }
+
+    /// The first player belongs to the device that controls it once it has been played
+    fn claim(
+        command: &mut Commands,
+        query: Query<(Entity, &Actions<Action>), (With<Player>, With<Unclaimed>)>,
+    ) {
+        for (player, actions) in query.iter() {
+            if actions.active().next().is_some() {
+                command.remove_one::<Unclaimed>(player);
+            }
+        }
+    }
+
+    /// Lets the devices that do not control a player yet join the game
+    ///
+    /// While the first player is unclaimed, any input of another device takes it over, so a
+    /// gamepad can play alone without touching the keyboard.
+    fn join(
+        frames: Res<InputFrames>,
+        mut join_events: ResMut<Events<JoinEvent>>,
+        players: Query<&InputDevice, With<Player>>,
+        unclaimed: Query<&Unclaimed, With<Player>>,
+    ) {
+        let claimable = unclaimed.iter().next().is_some();
+        for (&device, frame) in frames.devices.iter() {
+            let wants_to_join = frame.join || (claimable && !frame.is_idle());
+            if wants_to_join && players.iter().all(|&player| player != device) {
+                debug!("{:?} joins the game", device);
+                join_events.send(JoinEvent { device });
+            }
+        }
+    }
+}
+
+impl Plugin for InputPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        let bindings = KeyBindings::load(KeyBindings::PATH)
+            .map_err(|e| {
+                println!("{}", e);
+                e
+            })
+            .unwrap_or_default();
+        for action in bindings.unbound() {
+            warn!("No key bound to {:?}", action);
+        }
+        // `--script <file>` plays a script on the keyboard player
+        let scripted = arg_value("--script")
+            .and_then(|path| {
+                Script::from_file(&path)
+                    .map_err(|e| println!("Cannot load script {}: {}", path, e))
+                    .ok()
+            })
+            .map(|script| ScriptedInput::new(InputDevice::Keyboard, script))
+            .unwrap_or_default();
+        // Devices all write in the same frames, so the systems of the stages must stay in order
+        app.add_resource(bindings)
+            .init_resource::<InputFrames>()
+            .init_resource::<InputFocus>()
+            .init_resource::<InputSource>()
+            .add_resource(scripted)
+            .init_resource::<Cursor>()
+            .add_fixed_event::<JoinEvent>()
+            .add_stage_before(
+                stage::UPDATE,
+                Self::STAGE,
+                SystemStage::serial()
+                    .with_system(Self::begin_frame.system())
+                    .with_system(Self::keyboard.system())
+                    .with_system(Self::gamepad.system())
+                    .with_system(Self::mouse.system()),
+            )
+            .add_fixed_system(physic::INPUT_STAGE, Self::script.system())
+            .add_fixed_system(physic::INPUT_STAGE, Self::resolve.system())
+            .add_fixed_system(physic::INPUT_STAGE, Self::join.system())
+            .add_fixed_system(physic::UPDATE_STAGE, Self::claim.system());
+    }
+}
@@ -0,0 +1,93 @@
+use super::{Action, InputDevice, InputFrames};
+
+use serde::{Deserialize, Serialize};
+
+/// Actions held for a number of ticks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScriptStep {
+    pub ticks: u32,
+    #[serde(default)]
+    pub actions: Vec<Action>,
+}
+
+/// Sequence of inputs, for tests and playtest bots
+///
+/// The actions of each step are pressed on a device like keys would be, so they go through the
+/// same resolution as the inputs of a human player.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Script {
+    pub steps: Vec<ScriptStep>,
+}
+
+// Scripts are read from files by the game, building them from code is for the tests
+#[cfg_attr(not(test), allow(dead_code))]
+impl Script {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Holds the actions for the given number of ticks
+    pub fn hold(mut self, ticks: u32, actions: &[Action]) -> Self {
+        self.steps.push(ScriptStep {
+            ticks,
+            actions: actions.to_vec(),
+        });
+        self
+    }
+
+    /// Holds nothing for the given number of ticks
+    pub fn wait(self, ticks: u32) -> Self {
+        self.hold(ticks, &[])
+    }
+}
+
+/// Script being played on a device, one tick per fixed step
+#[derive(Debug, Default)]
+pub struct ScriptedInput {
+    device: Option<InputDevice>,
+    script: Script,
+    step: usize,
+    tick: u32,
+}
+
+impl ScriptedInput {
+    pub fn new(device: InputDevice, script: Script) -> Self {
+        Self {
+            device: Some(device),
+            script,
+            step: 0,
+            tick: 0,
+        }
+    }
+
+    // Only the tests wait for a script to end, the game keeps running once it is over
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn finished(&self) -> bool {
+        self.step >= self.script.steps.len()
+    }
+
+    /// Actions of the current tick, moving on to the next one
+    fn advance(&mut self) -> Option<&[Action]> {
+        while self
+            .script
+            .steps
+            .get(self.step)
+            .map_or(false, |step| self.tick >= step.ticks)
+        {
+            self.step += 1;
+            self.tick = 0;
+        }
+        let step = self.script.steps.get(self.step)?;
+        self.tick += 1;
+        Some(&step.actions)
+    }
+
+    /// Presses the actions of the current tick, replacing the ones of the previous tick as
+    /// several steps can read the same frame
+    pub(super) fn run(&mut self, frames: &mut InputFrames) {
+        if let Some(device) = self.device {
+            let actions = self.advance().map(<[Action]>::to_vec).unwrap_or_default();
+            frames.devices.entry(device).or_default().scripted = actions;
+        }
+    }
+}
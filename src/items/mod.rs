@@ -5,11 +5,27 @@
 mod tiny_planet;
 
 use bevy::{app::PluginGroupBuilder, prelude::*};
+use serde::{Deserialize, Serialize};
 pub use tiny_planet::TinyPlanet;
 
 /// Tag component identifying an item
 // pub struct Item;
 
+/// Items that can be referred to from data files, like the starting items of a character
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ItemKind {
+    TinyPlanet,
+}
+
+impl ItemKind {
+    /// Gives the item to the entity currently built by the commands
+    pub fn give(&self, command: &mut Commands) {
+        match self {
+            ItemKind::TinyPlanet => command.with(TinyPlanet),
+        };
+    }
+}
+
 pub struct ItemPlugins;
 
 pub struct ItemPickupEvent {
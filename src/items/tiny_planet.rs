@@ -1,9 +1,9 @@
 use bevy::prelude::*;
 
 use crate::{
-    physic::{self, Velocity},
+    physic::{self, FixedStep, Position, Velocity},
     player::Player,
-    weapons::TearTag,
+    weapons::{Shooter, TearTag},
 };
 
 #[derive(Debug, Default, Clone, Copy)]
@@ -17,35 +17,37 @@ impl TinyPlanet {
     }
 
     fn update(
-        mut tear_query: Query<(&Transform, &mut Velocity), With<TearTag>>,
-        player_query: Query<&Transform, (With<Player>, With<TinyPlanet>)>,
+        mut tear_query: Query<(&Shooter, &Position, &mut Velocity), With<TearTag>>,
+        player_query: Query<&Position, (With<Player>, With<TinyPlanet>)>,
     ) {
         let dt = physic::TIMESTEP as f32;
-        if let Some(Transform { translation, .. }) = player_query.iter().next() {
-            let player = translation.truncate();
-            for (transform, mut velocity) in tear_query.iter_mut() {
-                let velocity = &mut velocity.0;
-                let pos = transform.translation.truncate();
-                let radius = (pos - player).length();
-
-                if radius < f32::EPSILON {
-                    continue;
-                }
+        for (&Shooter(shooter), position, mut velocity) in tear_query.iter_mut() {
+            // Tears orbit the player that shot them, if they have the item
+            let player = match player_query.get(shooter) {
+                Ok(player) => player.current,
+                Err(_) => continue,
+            };
+            let velocity = &mut velocity.0;
+            let pos = position.current;
+            let radius = (pos - player).length();
+
+            if radius < f32::EPSILON {
+                continue;
+            }
 
-                let radial = (pos - player) / radius;
-                let tangent = Self::orthogonal(radial);
-                let speed = velocity.length();
+            let radial = (pos - player) / radius;
+            let tangent = Self::orthogonal(radial);
+            let speed = velocity.length();
 
-                let error = 1.0 - pos.distance(player) / Self::RADIUS_TARGET;
+            let error = 1.0 - pos.distance(player) / Self::RADIUS_TARGET;
 
-                *velocity = (tangent + 60.0 * error * dt * radial).normalize() * speed;
-            }
+            *velocity = (tangent + 60.0 * error * dt * radial).normalize() * speed;
         }
     }
 }
 
 impl Plugin for TinyPlanet {
     fn build(&self, app: &mut AppBuilder) {
-        app.add_system_to_stage(physic::STAGE, Self::update.system());
+        app.add_fixed_system(physic::UPDATE_STAGE, Self::update.system());
     }
 }
@@ -1,25 +1,35 @@
 mod animation;
 mod attribute;
+mod character;
+mod health;
 mod init;
 mod input;
 mod items;
 mod physic;
 mod player;
+mod rebind;
 mod render;
+mod replay;
+mod rng;
 mod ui;
 mod weapons;
 
 use crate::animation::AnimationPlugin;
 use crate::attribute::AttributesPlugin;
+use crate::health::HealthPlugin;
 use crate::init::InitPlugin;
 use crate::input::InputPlugin;
 use crate::items::ItemPlugins;
-use crate::physic::PhysicPlugin;
+use crate::physic::{PhysicDebugPlugin, PhysicPlugin};
 use crate::player::PlayerPlugin;
+use crate::rebind::RebindPlugin;
+use crate::render::RenderPlugin;
+use crate::replay::ReplayPlugin;
 use crate::ui::UIPlugin;
 use crate::weapons::WeaponPlugins;
 
-use bevy::{input::system::exit_on_esc_system, prelude::*};
+use bevy::{app::ScheduleRunnerSettings, input::system::exit_on_esc_system, prelude::*};
+use std::time::Duration;
 
 trait FromRon: Sized {
     fn from_file(path: &str) -> Result<Self, ron::Error>;
@@ -34,25 +44,121 @@ where
     }
 }
 
-#[bevy_main]
-fn main() {
-    App::build()
-        .add_resource(WindowDescriptor {
-            title: "Ferris's Tears".to_string(),
-            vsync: false,
-            ..Default::default()
-        })
-        .add_resource(ClearColor(Color::rgb(0.16, 0.16, 0.16)))
-        .add_plugins(DefaultPlugins)
+/// Value given to a command line option, like `<file>` in `--record <file>`
+fn arg_value(option: &str) -> Option<String> {
+    std::env::args().skip_while(|arg| arg != option).nth(1)
+}
+
+/// Plugins running the game logic, which need neither a window nor a GPU
+///
+/// The physics plugin comes first as it creates the fixed step stage the others add systems to.
+fn add_gameplay_plugins(app: &mut AppBuilder) -> &mut AppBuilder {
+    app.add_plugin(PhysicPlugin)
         .add_plugin(InitPlugin)
         .add_plugin(AttributesPlugin)
+        .add_plugin(HealthPlugin)
         .add_plugin(InputPlugin)
         .add_plugin(AnimationPlugin)
-        .add_plugin(PhysicPlugin)
         .add_plugin(PlayerPlugin)
         .add_plugins(WeaponPlugins)
         .add_plugins(ItemPlugins)
+        .add_plugin(ReplayPlugin)
+}
+
+/// Builds the game without window nor rendering, to run the game loop in CI or in tests
+fn build_simulation_app() -> AppBuilder {
+    let mut app = App::build();
+    app.add_resource(ScheduleRunnerSettings::run_loop(Duration::from_secs_f64(
+        physic::TIMESTEP,
+    )))
+    .add_plugins(MinimalPlugins)
+    .add_plugin(bevy::input::InputPlugin);
+    add_gameplay_plugins(&mut app);
+    app
+}
+
+fn build_app() -> AppBuilder {
+    let mut app = App::build();
+    app.add_resource(WindowDescriptor {
+        title: "Ferris's Tears".to_string(),
+        vsync: false,
+        ..Default::default()
+    })
+    .add_resource(ClearColor(Color::rgb(0.16, 0.16, 0.16)))
+    .add_plugins(DefaultPlugins);
+    add_gameplay_plugins(&mut app)
+        .add_plugin(RenderPlugin)
+        .add_plugin(PhysicDebugPlugin)
         .add_plugin(UIPlugin)
-        .add_system(exit_on_esc_system.system())
-        .run();
+        .add_plugin(RebindPlugin)
+        .add_system(exit_on_esc_system.system());
+    app
+}
+
+#[bevy_main]
+fn main() {
+    if std::env::args().any(|arg| arg == "--headless") {
+        build_simulation_app().run();
+    } else {
+        build_app().run();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::{Action, Actions, InputDevice, Script, ScriptedInput};
+    use crate::physic::{FixedStep, Position};
+    use crate::player::Player;
+
+    #[test]
+    fn simulation_runs_without_window() {
+        let mut app = build_simulation_app();
+        for _ in 0..10 {
+            app.app.update();
+        }
+        let players = app.app.world.query::<(&Player, &Position)>().count();
+        assert_eq!(1, players);
+    }
+
+    /// Whether the player was moving at each fixed step
+    #[derive(Default)]
+    struct MovingSteps(Vec<bool>);
+
+    fn record_moving(mut steps: ResMut<MovingSteps>, query: Query<&Actions<Action>, With<Player>>) {
+        let moving = query
+            .iter()
+            .any(|actions| actions.get(Action::Move).is_some());
+        steps.0.push(moving);
+    }
+
+    #[test]
+    fn scripted_input_drives_player_actions() {
+        let right = Action::Move(Vec2::new(1.0, 0.0));
+        let script = Script::new().wait(1).hold(3, &[right]);
+        let mut app = build_simulation_app();
+        app.add_resource(ScriptedInput::new(InputDevice::Keyboard, script))
+            .init_resource::<MovingSteps>()
+            .add_fixed_system(physic::UPDATE_STAGE, record_moving.system());
+
+        let finished = |app: &App| {
+            app.resources
+                .get::<ScriptedInput>()
+                .map_or(false, |scripted| scripted.finished())
+        };
+
+        // Any number of steps can run in an update, the actions are checked at every step
+        for _ in 0..100 {
+            if finished(&app.app) {
+                break;
+            }
+            std::thread::sleep(Duration::from_secs_f64(physic::TIMESTEP));
+            app.app.update();
+        }
+        assert!(finished(&app.app));
+
+        let steps = app.app.resources.get::<MovingSteps>().unwrap();
+        assert_eq!(&steps.0[..5], &[false, true, true, true, false]);
+        assert!(steps.0[5..].iter().all(|&moving| !moving));
+    }
 }
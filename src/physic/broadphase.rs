@@ -0,0 +1,63 @@
+use super::layers::CollisionGroups;
+use bevy::prelude::*;
+use std::cmp::Ordering;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    pub min: Vec2,
+    pub max: Vec2,
+}
+
+impl Aabb {
+    pub fn overlaps(&self, other: &Aabb) -> bool {
+        self.min.x <= other.max.x
+            && other.min.x <= self.max.x
+            && self.min.y <= other.max.y
+            && other.min.y <= self.max.y
+    }
+
+    pub fn union(&self, other: &Aabb) -> Aabb {
+        Aabb {
+            min: self.min.min(other.min),
+            max: self.max.max(other.max),
+        }
+    }
+}
+
+/// Sort-and-sweep broadphase along the x axis
+///
+/// Proxies are sorted by the left side of their bounding box, so each proxy only has to be tested
+/// against the following ones until their left side goes past its right side. Pairs whose
+/// collision groups do not accept each other are discarded.
+#[derive(Default)]
+pub struct BroadPhase {
+    proxies: Vec<(Entity, Aabb, CollisionGroups)>,
+    pairs: Vec<(Entity, Entity)>,
+}
+
+impl BroadPhase {
+    pub fn update(&mut self, proxies: impl Iterator<Item = (Entity, Aabb, CollisionGroups)>) {
+        self.proxies.clear();
+        self.proxies.extend(proxies);
+        self.proxies.sort_by(|(_, a, _), (_, b, _)| {
+            a.min.x.partial_cmp(&b.min.x).unwrap_or(Ordering::Equal)
+        });
+
+        self.pairs.clear();
+        for (i, &(a, aabb_a, groups_a)) in self.proxies.iter().enumerate() {
+            for &(b, aabb_b, groups_b) in &self.proxies[i + 1..] {
+                if aabb_b.min.x > aabb_a.max.x {
+                    break;
+                }
+                if groups_a.interacts_with(&groups_b) && aabb_a.overlaps(&aabb_b) {
+                    self.pairs.push(if a < b { (a, b) } else { (b, a) });
+                }
+            }
+        }
+    }
+
+    /// Potentially colliding pairs found by the last update, with the smallest entity first
+    pub fn pairs(&self) -> &[(Entity, Entity)] {
+        &self.pairs
+    }
+}
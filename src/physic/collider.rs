@@ -0,0 +1,370 @@
+use bevy::prelude::*;
+
+use super::{broadphase::Aabb, layers::CollisionGroups};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Shape {
+    Circle { radius: f32 },
+    Aabb { half_extents: Vec2 },
+}
+
+impl Shape {
+    /// Bounding box of the shape centered on `position`
+    pub fn aabb(&self, position: Vec2) -> Aabb {
+        let half_extents = match *self {
+            Shape::Circle { radius } => Vec2::splat(radius),
+            Shape::Aabb { half_extents } => half_extents,
+        };
+        Aabb {
+            min: position - half_extents,
+            max: position + half_extents,
+        }
+    }
+
+    /// Furthest point of the shape in the given direction, relative to its center
+    pub fn support(&self, direction: Vec2) -> Vec2 {
+        match *self {
+            Shape::Circle { radius } if direction.length_squared() > f32::EPSILON => {
+                direction.normalize() * radius
+            }
+            Shape::Circle { .. } => Vec2::zero(),
+            Shape::Aabb { half_extents } => {
+                let sign = |v: f32| {
+                    if v.abs() <= f32::EPSILON {
+                        0.0
+                    } else {
+                        v.signum()
+                    }
+                };
+                Vec2::new(
+                    sign(direction.x) * half_extents.x,
+                    sign(direction.y) * half_extents.y,
+                )
+            }
+        }
+    }
+
+    /// Every supported shape is a box with rounded corners, given as its half extents and radius
+    fn rounded_box(&self) -> (Vec2, f32) {
+        match *self {
+            Shape::Circle { radius } => (Vec2::zero(), radius),
+            Shape::Aabb { half_extents } => (half_extents, 0.0),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Collider {
+    pub shape: Shape,
+    /// Offset of the shape relative to the entity position
+    pub offset: Vec2,
+    pub groups: CollisionGroups,
+}
+
+impl Collider {
+    pub fn circle(radius: f32) -> Self {
+        Self {
+            shape: Shape::Circle { radius },
+            offset: Vec2::zero(),
+            groups: CollisionGroups::ALL,
+        }
+    }
+
+    pub fn aabb(half_extents: Vec2) -> Self {
+        Self {
+            shape: Shape::Aabb { half_extents },
+            offset: Vec2::zero(),
+            groups: CollisionGroups::ALL,
+        }
+    }
+
+    pub fn with_offset(mut self, offset: Vec2) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    pub fn with_groups(mut self, groups: CollisionGroups) -> Self {
+        self.groups = groups;
+        self
+    }
+
+    /// Center of the collider shape for an entity at `position`
+    pub fn center(&self, position: Vec2) -> Vec2 {
+        position + self.offset
+    }
+}
+
+/// Result of the narrowphase between two colliders
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Contact {
+    /// Unit vector pointing from the first collider towards the second one
+    pub normal: Vec2,
+    /// Penetration depth along the normal
+    pub depth: f32,
+    /// Point on the surface of the first collider
+    pub point: Vec2,
+}
+
+impl Contact {
+    /// Same contact seen from the second collider
+    pub fn flipped(self) -> Self {
+        Self {
+            normal: -self.normal,
+            depth: self.depth,
+            point: self.point - self.normal * self.depth,
+        }
+    }
+}
+
+/// Computes the contact between shape `a` centered at `pa` and shape `b` centered at `pb`
+pub fn contact(a: &Shape, pa: Vec2, b: &Shape, pb: Vec2) -> Option<Contact> {
+    match (*a, *b) {
+        (Shape::Circle { radius: ra }, Shape::Circle { radius: rb }) => {
+            circle_circle(pa, ra, pb, rb)
+        }
+        (Shape::Aabb { half_extents: ha }, Shape::Aabb { half_extents: hb }) => {
+            aabb_aabb(pa, ha, pb, hb)
+        }
+        (Shape::Circle { radius }, Shape::Aabb { half_extents }) => {
+            aabb_circle(pb, half_extents, pa, radius).map(Contact::flipped)
+        }
+        (Shape::Aabb { half_extents }, Shape::Circle { radius }) => {
+            aabb_circle(pa, half_extents, pb, radius)
+        }
+    }
+}
+
+fn circle_circle(pa: Vec2, ra: f32, pb: Vec2, rb: f32) -> Option<Contact> {
+    let delta = pb - pa;
+    let distance = delta.length();
+    if distance >= ra + rb {
+        return None;
+    }
+
+    let normal = if distance > f32::EPSILON {
+        delta / distance
+    } else {
+        Vec2::unit_x()
+    };
+    Some(Contact {
+        normal,
+        depth: ra + rb - distance,
+        point: pa + normal * ra,
+    })
+}
+
+fn aabb_aabb(pa: Vec2, ha: Vec2, pb: Vec2, hb: Vec2) -> Option<Contact> {
+    let delta = pb - pa;
+    let overlap_x = ha.x + hb.x - delta.x.abs();
+    let overlap_y = ha.y + hb.y - delta.y.abs();
+    if overlap_x <= 0.0 || overlap_y <= 0.0 {
+        return None;
+    }
+
+    let sign = |v: f32| if v < 0.0 { -1.0 } else { 1.0 };
+    if overlap_x < overlap_y {
+        let normal = Vec2::new(sign(delta.x), 0.0);
+        Some(Contact {
+            normal,
+            depth: overlap_x,
+            point: Vec2::new(
+                pa.x + normal.x * ha.x,
+                pb.y.max(pa.y - ha.y).min(pa.y + ha.y),
+            ),
+        })
+    } else {
+        let normal = Vec2::new(0.0, sign(delta.y));
+        Some(Contact {
+            normal,
+            depth: overlap_y,
+            point: Vec2::new(
+                pb.x.max(pa.x - ha.x).min(pa.x + ha.x),
+                pa.y + normal.y * ha.y,
+            ),
+        })
+    }
+}
+
+fn aabb_circle(pa: Vec2, ha: Vec2, pb: Vec2, rb: f32) -> Option<Contact> {
+    let local = pb - pa;
+    let closest = local.max(-ha).min(ha);
+    let inside = closest == local;
+
+    if !inside {
+        let delta = local - closest;
+        let distance = delta.length();
+        if distance >= rb {
+            return None;
+        }
+        return Some(Contact {
+            normal: delta / distance,
+            depth: rb - distance,
+            point: pa + closest,
+        });
+    }
+
+    // The circle center is inside the box, push it out through the closest face
+    let to_x = ha.x - local.x.abs();
+    let to_y = ha.y - local.y.abs();
+    let sign = |v: f32| if v < 0.0 { -1.0 } else { 1.0 };
+    if to_x < to_y {
+        let normal = Vec2::new(sign(local.x), 0.0);
+        Some(Contact {
+            normal,
+            depth: to_x + rb,
+            point: pa + Vec2::new(normal.x * ha.x, local.y),
+        })
+    } else {
+        let normal = Vec2::new(0.0, sign(local.y));
+        Some(Contact {
+            normal,
+            depth: to_y + rb,
+            point: pa + Vec2::new(local.x, normal.y * ha.y),
+        })
+    }
+}
+
+/// First hit of a shape moving along a path
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CastHit {
+    /// Fraction of the motion travelled before the hit
+    pub toi: f32,
+    /// Normal of the hit surface, pointing towards the moving shape
+    pub normal: Vec2,
+}
+
+/// Moves shape `a` from `pa` by `motion` and returns its first hit with shape `b` centered at `pb`
+///
+/// The cast is done as a ray against the Minkowski difference of both shapes, which is still a
+/// rounded box. Shapes overlapping at the start of the motion hit with a time of impact of zero.
+pub fn cast(a: &Shape, pa: Vec2, motion: Vec2, b: &Shape, pb: Vec2) -> Option<CastHit> {
+    let (ha, ra) = a.rounded_box();
+    let (hb, rb) = b.rounded_box();
+    ray_rounded_box(pa - pb, motion, ha + hb, ra + rb)
+}
+
+/// Same as [cast](cast) but returns the contact at the time of impact, with no penetration
+pub fn swept_contact(a: &Shape, pa: Vec2, motion: Vec2, b: &Shape, pb: Vec2) -> Option<Contact> {
+    let hit = cast(a, pa, motion, b, pb)?;
+    Some(Contact {
+        normal: -hit.normal,
+        depth: 0.0,
+        point: pa + motion * hit.toi + a.support(-hit.normal),
+    })
+}
+
+/// Casts a ray from `origin` (relative to the box center) along `motion`, up to `origin + motion`
+fn ray_rounded_box(origin: Vec2, motion: Vec2, half_extents: Vec2, radius: f32) -> Option<CastHit> {
+    let closest = origin.max(-half_extents).min(half_extents);
+    if (origin - closest).length_squared() <= radius * radius {
+        let normal = if motion.length_squared() > f32::EPSILON {
+            -motion.normalize()
+        } else {
+            Vec2::unit_x()
+        };
+        return Some(CastHit { toi: 0.0, normal });
+    }
+
+    // Slab test against the box expanded by the radius
+    let outer = half_extents + Vec2::splat(radius);
+    let mut toi_min = 0.0f32;
+    let mut toi_max = 1.0f32;
+    let mut normal = Vec2::zero();
+    let axes = [
+        (origin.x, motion.x, outer.x, Vec2::unit_x()),
+        (origin.y, motion.y, outer.y, Vec2::unit_y()),
+    ];
+    for &(o, d, h, axis) in axes.iter() {
+        if d.abs() < f32::EPSILON {
+            if o.abs() > h {
+                return None;
+            }
+            continue;
+        }
+        let (enter, exit) = ((-h - o) / d, (h - o) / d);
+        let (enter, exit) = if enter < exit {
+            (enter, exit)
+        } else {
+            (exit, enter)
+        };
+        if enter > toi_min {
+            toi_min = enter;
+            normal = if d > 0.0 { -axis } else { axis };
+        }
+        toi_max = toi_max.min(exit);
+        if toi_min > toi_max {
+            return None;
+        }
+    }
+
+    // Hits in a corner region have to be checked against the rounded corner
+    let point = origin + motion * toi_min;
+    if radius > 0.0 && point.x.abs() > half_extents.x && point.y.abs() > half_extents.y {
+        let corner = Vec2::new(
+            half_extents.x.copysign(point.x),
+            half_extents.y.copysign(point.y),
+        );
+        return ray_circle(origin - corner, motion, radius);
+    }
+
+    Some(CastHit {
+        toi: toi_min,
+        normal,
+    })
+}
+
+/// Casts a ray from `origin` (relative to the circle center) along `motion`
+fn ray_circle(origin: Vec2, motion: Vec2, radius: f32) -> Option<CastHit> {
+    let a = motion.length_squared();
+    let b = origin.dot(motion);
+    let c = origin.length_squared() - radius * radius;
+    let discriminant = b * b - a * c;
+    if a < f32::EPSILON || discriminant < 0.0 {
+        return None;
+    }
+
+    let toi = (-b - discriminant.sqrt()) / a;
+    if !(0.0..=1.0).contains(&toi) {
+        return None;
+    }
+    Some(CastHit {
+        toi,
+        normal: (origin + motion * toi) / radius,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn circle_against_box_points_from_first_to_second() {
+        let circle = Shape::Circle { radius: 1.0 };
+        let aabb = Shape::Aabb {
+            half_extents: Vec2::new(2.0, 2.0),
+        };
+        let hit = contact(&circle, Vec2::new(-2.5, 0.0), &aabb, Vec2::zero()).unwrap();
+        assert_eq!(Vec2::new(1.0, 0.0), hit.normal);
+        assert!((hit.depth - 0.5).abs() < 1e-5);
+        assert!(contact(&circle, Vec2::new(-3.5, 0.0), &aabb, Vec2::zero()).is_none());
+    }
+
+    #[test]
+    fn fast_circle_cannot_tunnel_through_thin_box() {
+        let tear = Shape::Circle { radius: 1.0 };
+        let wall = Shape::Aabb {
+            half_extents: Vec2::new(0.5, 10.0),
+        };
+        let start = Vec2::new(-20.0, 0.0);
+        let motion = Vec2::new(40.0, 0.0);
+        assert!(contact(&tear, start + motion, &wall, Vec2::zero()).is_none());
+
+        let hit = cast(&tear, start, motion, &wall, Vec2::zero()).unwrap();
+        assert!((hit.toi - 18.5 / 40.0).abs() < 1e-5);
+        assert_eq!(Vec2::new(-1.0, 0.0), hit.normal);
+
+        let corner = cast(&tear, Vec2::new(-20.0, 10.9), motion, &wall, Vec2::zero()).unwrap();
+        assert!(corner.normal.y > 0.0);
+        assert!(cast(&tear, Vec2::new(-20.0, 11.5), motion, &wall, Vec2::zero()).is_none());
+    }
+}
@@ -0,0 +1,157 @@
+use super::{
+    BroadPhase, Collider, Collisions, FixedStep, Knockback, PhysicPlugin, Position, Shape,
+    Velocity, INTERPOLATION_STAGE, UPDATE_STAGE,
+};
+use crate::input::{Action, Actions};
+
+use bevy::{core::FixedTimesteps, prelude::*};
+
+/// State of the physics debug overlay
+#[derive(Debug, Default)]
+pub struct PhysicDebug {
+    pub enabled: bool,
+}
+
+impl PhysicDebug {
+    /// Text appended to the frame counter while the overlay is shown
+    pub fn stats(&self, broad_phase: &BroadPhase) -> Option<String> {
+        if self.enabled {
+            Some(format!("pairs: {}", broad_phase.pairs().len()))
+        } else {
+            None
+        }
+    }
+}
+
+struct DebugMaterials {
+    collider: Handle<ColorMaterial>,
+    velocity: Handle<ColorMaterial>,
+    contact: Handle<ColorMaterial>,
+}
+
+/// Tag of the sprites drawn by the overlay, they only live for a frame
+struct DebugShape;
+
+/// Draws colliders, velocities and contact points over the game
+pub struct PhysicDebugPlugin;
+
+impl PhysicDebugPlugin {
+    const DEPTH: f32 = 500.0;
+    const THICKNESS: f32 = 3.0;
+    const CIRCLE_SEGMENTS: usize = 16;
+    /// Duration represented by the velocity vectors
+    const VELOCITY_SCALE: f32 = 0.1;
+
+    fn setup(command: &mut Commands, mut materials: ResMut<Assets<ColorMaterial>>) {
+        command.insert_resource(DebugMaterials {
+            collider: materials.add(Color::rgb(0.2, 1.0, 0.2).into()),
+            velocity: materials.add(Color::rgb(0.2, 0.6, 1.0).into()),
+            contact: materials.add(Color::rgb(1.0, 0.2, 0.2).into()),
+        });
+    }
+
+    fn toggle(players: Query<&Actions<Action>>, mut debug: ResMut<PhysicDebug>) {
+        if players
+            .iter()
+            .any(|actions| actions.just_triggered(Action::Debug))
+        {
+            debug.enabled = !debug.enabled;
+        }
+    }
+
+    fn line(command: &mut Commands, material: &Handle<ColorMaterial>, from: Vec2, to: Vec2) {
+        let delta = to - from;
+        command
+            .spawn(SpriteBundle {
+                sprite: Sprite::new(Vec2::new(delta.length(), Self::THICKNESS)),
+                material: material.clone(),
+                transform: Transform {
+                    translation: (0.5 * (from + to)).extend(Self::DEPTH),
+                    rotation: Quat::from_rotation_z(delta.y.atan2(delta.x)),
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .with(DebugShape);
+    }
+
+    fn outline(command: &mut Commands, material: &Handle<ColorMaterial>, shape: &Shape, at: Vec2) {
+        match *shape {
+            Shape::Circle { radius } => {
+                let step = 2.0 * std::f32::consts::PI / Self::CIRCLE_SEGMENTS as f32;
+                let point = |i: usize| {
+                    let angle = i as f32 * step;
+                    at + radius * Vec2::new(angle.cos(), angle.sin())
+                };
+                for i in 0..Self::CIRCLE_SEGMENTS {
+                    Self::line(command, material, point(i), point(i + 1));
+                }
+            }
+            Shape::Aabb { half_extents } => {
+                let corners = [
+                    at + Vec2::new(-half_extents.x, -half_extents.y),
+                    at + Vec2::new(half_extents.x, -half_extents.y),
+                    at + Vec2::new(half_extents.x, half_extents.y),
+                    at + Vec2::new(-half_extents.x, half_extents.y),
+                ];
+                let next = corners.iter().cycle().skip(1);
+                for (&from, &to) in corners.iter().zip(next) {
+                    Self::line(command, material, from, to);
+                }
+            }
+        }
+    }
+
+    fn draw(
+        command: &mut Commands,
+        debug: Res<PhysicDebug>,
+        fixed_timesteps: Res<FixedTimesteps>,
+        materials: Res<DebugMaterials>,
+        collisions: Res<Collisions>,
+        shapes: Query<Entity, With<DebugShape>>,
+        bodies: Query<(&Collider, &Position, Option<&Velocity>, Option<&Knockback>)>,
+    ) {
+        for e in shapes.iter() {
+            command.despawn(e);
+        }
+
+        if !debug.enabled {
+            return;
+        }
+
+        // Drawn where the sprites are rendered, between the two last physics states
+        let alpha = PhysicPlugin::overstep(&fixed_timesteps);
+        for (collider, position, velocity, knockback) in bodies.iter() {
+            let center = collider.center(position.previous.lerp(position.current, alpha));
+            Self::outline(command, &materials.collider, &collider.shape, center);
+
+            let velocity = velocity.map_or(Vec2::zero(), |velocity| velocity.0)
+                + knockback.map_or(Vec2::zero(), |knockback| knockback.velocity);
+            if velocity.length_squared() > f32::EPSILON {
+                let tip = center + Self::VELOCITY_SCALE * velocity;
+                Self::line(command, &materials.velocity, center, tip);
+            }
+        }
+
+        for (_, _, contact) in collisions.iter() {
+            let size = Vec2::splat(4.0 * Self::THICKNESS);
+            command
+                .spawn(SpriteBundle {
+                    sprite: Sprite::new(size),
+                    material: materials.contact.clone(),
+                    transform: Transform::from_translation(contact.point.extend(Self::DEPTH)),
+                    ..Default::default()
+                })
+                .with(DebugShape);
+        }
+    }
+}
+
+impl Plugin for PhysicDebugPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<PhysicDebug>()
+            .add_startup_system(Self::setup.system())
+            .add_fixed_system(UPDATE_STAGE, Self::toggle.system())
+            .add_system_to_stage(INTERPOLATION_STAGE, Self::draw.system());
+    }
+}
@@ -0,0 +1,76 @@
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Bitsets deciding which colliders can interact with each other
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CollisionGroups {
+    /// Layers the collider belongs to
+    pub memberships: u32,
+    /// Layers the collider can collide with
+    pub filter: u32,
+}
+
+impl CollisionGroups {
+    pub const ALL: Self = Self {
+        memberships: u32::MAX,
+        filter: u32::MAX,
+    };
+
+    /// Both colliders must accept each other for the pair to be considered
+    pub fn interacts_with(&self, other: &Self) -> bool {
+        self.memberships & other.filter != 0 && other.memberships & self.filter != 0
+    }
+}
+
+impl Default for CollisionGroups {
+    fn default() -> Self {
+        Self::ALL
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CollisionProfile {
+    pub member_of: Vec<String>,
+    pub collides_with: Vec<String>,
+}
+
+/// Named collision layers and the profiles built from them
+///
+/// Layers are assigned a bit in declaration order, so at most 32 layers can be declared.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CollisionLayers {
+    layers: Vec<String>,
+    profiles: HashMap<String, CollisionProfile>,
+}
+
+impl CollisionLayers {
+    pub fn layer(&self, name: &str) -> u32 {
+        match self.layers.iter().position(|layer| layer == name) {
+            Some(index) if index < 32 => 1 << index,
+            _ => {
+                warn!("Unknown collision layer {:?}", name);
+                0
+            }
+        }
+    }
+
+    pub fn mask<S: AsRef<str>>(&self, names: &[S]) -> u32 {
+        names
+            .iter()
+            .fold(0, |mask, name| mask | self.layer(name.as_ref()))
+    }
+
+    /// Groups of the given profile, colliding with everything if the profile is not declared
+    pub fn profile(&self, name: &str) -> CollisionGroups {
+        if let Some(profile) = self.profiles.get(name) {
+            CollisionGroups {
+                memberships: self.mask(&profile.member_of),
+                filter: self.mask(&profile.collides_with),
+            }
+        } else {
+            warn!("Unknown collision profile {:?}", name);
+            CollisionGroups::ALL
+        }
+    }
+}
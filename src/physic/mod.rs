@@ -0,0 +1,490 @@
+mod broadphase;
+mod collider;
+mod debug;
+mod layers;
+mod query;
+mod terrain;
+
+pub use broadphase::{Aabb, BroadPhase};
+pub use collider::{cast, contact, swept_contact, CastHit, Collider, Contact, Shape};
+pub use debug::{PhysicDebug, PhysicDebugPlugin};
+pub use layers::{CollisionGroups, CollisionLayers, CollisionProfile};
+pub use query::{SpatialHit, SpatialQuery};
+pub use terrain::{Flying, Terrain};
+
+use crate::FromRon;
+
+use bevy::{
+    core::{FixedTimestep, FixedTimesteps},
+    prelude::*,
+};
+use std::collections::BTreeMap;
+
+/// Position of a body in the simulation
+///
+/// The rendered `Transform` is interpolated between the previous and the current position, so
+/// gameplay code should read and write positions rather than transforms.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Position {
+    pub current: Vec2,
+    /// Position before the last integration step
+    pub previous: Vec2,
+}
+
+impl Position {
+    pub fn new(position: Vec2) -> Self {
+        Self {
+            current: position,
+            previous: position,
+        }
+    }
+
+    /// Moves the body without interpolating nor sweeping from its last position
+    pub fn teleport(&mut self, position: Vec2) {
+        *self = Self::new(position);
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Velocity(pub Vec2);
+
+#[derive(Debug, Default)]
+pub struct Movement {
+    pub direction: Option<Vec2>,
+    pub speed: f32,
+    pub acceleration: f32,
+    pub damping: f32,
+}
+
+/// External velocity added on top of the steered [Velocity](Velocity), which is not affected by
+/// the [Movement](Movement) of the body and slows down on its own
+#[derive(Debug, Clone, Copy)]
+pub struct Knockback {
+    pub velocity: Vec2,
+    /// Deceleration of the knockback, in units per second squared
+    pub decay: f32,
+}
+
+impl Knockback {
+    pub fn new(decay: f32) -> Self {
+        Self {
+            velocity: Vec2::zero(),
+            decay,
+        }
+    }
+}
+
+impl Default for Knockback {
+    fn default() -> Self {
+        Self::new(4000.0)
+    }
+}
+
+/// Resistance of a body to impulses, bodies without a mass weigh 1
+#[derive(Debug, Clone, Copy)]
+pub struct Mass(pub f32);
+
+impl Default for Mass {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+/// Event pushing a body with a [Knockback](Knockback), changing its knockback velocity by
+/// `impulse / mass`
+#[derive(Debug, Clone, Copy)]
+pub struct Impulse {
+    pub target: Entity,
+    pub impulse: Vec2,
+}
+
+/// Static collider that bodies with a [Movement](Movement) cannot penetrate
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Solid;
+
+/// Body swept between its positions before and after each integration step, so that it cannot
+/// tunnel through thin colliders whatever its speed
+#[derive(Debug, Default, Clone, Copy)]
+pub struct FastProjectile;
+
+/// Event sent when two colliders start overlapping
+#[derive(Debug, Clone, Copy)]
+pub struct CollisionStarted(pub Entity, pub Entity);
+
+/// Event sent when two colliders stop overlapping, or when one of them is removed
+#[derive(Debug, Clone, Copy)]
+pub struct CollisionEnded(pub Entity, pub Entity);
+
+/// Pairs of colliders currently overlapping, with the smallest entity first
+#[derive(Default)]
+pub struct Collisions(BTreeMap<(Entity, Entity), Contact>);
+
+impl Collisions {
+    fn key(a: Entity, b: Entity) -> (Entity, Entity) {
+        if a < b {
+            (a, b)
+        } else {
+            (b, a)
+        }
+    }
+
+    pub fn contains(&self, a: Entity, b: Entity) -> bool {
+        self.0.contains_key(&Self::key(a, b))
+    }
+
+    /// Contact between `a` and `b`, with the normal pointing from `a` towards `b`
+    pub fn get(&self, a: Entity, b: Entity) -> Option<Contact> {
+        let contact = *self.0.get(&Self::key(a, b))?;
+        if a < b {
+            Some(contact)
+        } else {
+            Some(contact.flipped())
+        }
+    }
+
+    /// Entities currently overlapping the given one
+    pub fn with(&self, entity: Entity) -> impl Iterator<Item = Entity> + '_ {
+        self.0.keys().filter_map(move |&(a, b)| {
+            if a == entity {
+                Some(b)
+            } else if b == entity {
+                Some(a)
+            } else {
+                None
+            }
+        })
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Entity, Entity, &Contact)> {
+        self.0.iter().map(|(&(a, b), contact)| (a, b, contact))
+    }
+}
+
+/// Stage running its sub stages in order, once per fixed step
+///
+/// Everything that must play out the same way in a replay runs there, whatever the frame rate.
+pub const STAGE: &str = "fixed_step";
+/// Sub stage of the fixed step turning the inputs into the actions of the players
+pub const INPUT_STAGE: &str = "fixed_input";
+/// Sub stage of the fixed step for the gameplay, before the physics
+pub const UPDATE_STAGE: &str = "fixed_update";
+/// Sub stage of the fixed step detecting the collisions, then integrating and resolving them
+pub const PHYSICS_STAGE: &str = "physics";
+/// Sub stage ending the fixed step, where the events of the step are updated
+const EVENT_STAGE: &str = "fixed_events";
+/// Stage placing the rendered transforms between the two last physics states
+pub const INTERPOLATION_STAGE: &str = "physics_interpolation";
+pub const TIMESTEP: f64 = 0.016;
+const TIMESTEP_LABEL: &str = "physics_timestep";
+
+/// Adds systems to the sub stages of the fixed step [STAGE](STAGE)
+pub trait FixedStep {
+    fn add_fixed_system<S: System<In = (), Out = ()>>(
+        &mut self,
+        stage: &'static str,
+        system: S,
+    ) -> &mut Self;
+
+    /// Adds an event read in the fixed step
+    ///
+    /// Events are dropped after two updates, so events updated every frame would be lost when
+    /// several frames go by without a step. These ones are updated at the end of every step.
+    fn add_fixed_event<T: Send + Sync + 'static>(&mut self) -> &mut Self;
+}
+
+impl FixedStep for AppBuilder {
+    fn add_fixed_system<S: System<In = (), Out = ()>>(
+        &mut self,
+        stage: &'static str,
+        system: S,
+    ) -> &mut Self {
+        self.stage(STAGE, |schedule: &mut Schedule| {
+            schedule.add_system_to_stage(stage, system)
+        })
+    }
+
+    fn add_fixed_event<T: Send + Sync + 'static>(&mut self) -> &mut Self {
+        self.add_resource(Events::<T>::default())
+            .add_fixed_system(EVENT_STAGE, Events::<T>::update_system.system())
+    }
+}
+
+pub struct PhysicPlugin;
+
+impl PhysicPlugin {
+    fn moving(
+        collisions: Res<Collisions>,
+        terrains: Query<&Terrain>,
+        mut query: Query<(Entity, &Movement, &mut Velocity, Option<&Flying>)>,
+    ) {
+        let dt = TIMESTEP as f32;
+        for (e, movement, mut velocity, flying) in query.iter_mut() {
+            let terrain = if flying.is_some() {
+                Terrain::default()
+            } else {
+                collisions
+                    .with(e)
+                    .filter_map(|other| terrains.get(other).ok())
+                    .fold(Terrain::default(), |terrain, &other| terrain.stack(other))
+            };
+
+            let is_moving = movement.direction.is_some();
+            let target = movement.speed * terrain.speed * movement.direction.unwrap_or_default();
+            let current = velocity.0;
+            let error = target - current;
+            let norm = error.length();
+            if norm > f32::EPSILON {
+                let coeff = if is_moving {
+                    movement.acceleration * terrain.acceleration
+                } else {
+                    movement.damping * terrain.damping
+                };
+                velocity.0 = if norm <= coeff * dt {
+                    target
+                } else {
+                    current + coeff * dt / norm * error
+                };
+            }
+        }
+    }
+
+    fn impulses(
+        mut event_reader: Local<EventReader<Impulse>>,
+        impulses: Res<Events<Impulse>>,
+        mut query: Query<(&mut Knockback, Option<&Mass>)>,
+    ) {
+        for &Impulse { target, impulse } in event_reader.iter(&impulses) {
+            if let Ok((mut knockback, mass)) = query.get_mut(target) {
+                let Mass(mass) = mass.copied().unwrap_or_default();
+                if mass > f32::EPSILON {
+                    knockback.velocity += impulse / mass;
+                }
+            }
+        }
+    }
+
+    fn knockback(mut query: Query<&mut Knockback>) {
+        let dt = TIMESTEP as f32;
+        for mut knockback in query.iter_mut() {
+            let speed = knockback.velocity.length();
+            if speed <= knockback.decay * dt {
+                knockback.velocity = Vec2::zero();
+            } else {
+                let decay = knockback.decay;
+                knockback.velocity *= 1.0 - decay * dt / speed;
+            }
+        }
+    }
+
+    fn collisions(
+        mut broad_phase: ResMut<BroadPhase>,
+        mut collisions: ResMut<Collisions>,
+        mut started: ResMut<Events<CollisionStarted>>,
+        mut ended: ResMut<Events<CollisionEnded>>,
+        query: Query<(Entity, &Collider, &Position, Option<&FastProjectile>)>,
+    ) {
+        // Colliders centers before and after the last integration step
+        let endpoints =
+            |collider: &Collider, position: &Position, fast: Option<&FastProjectile>| {
+                let center = collider.center(position.current);
+                if fast.is_some() {
+                    (collider.center(position.previous), center)
+                } else {
+                    (center, center)
+                }
+            };
+
+        broad_phase.update(query.iter().map(|(e, collider, position, fast)| {
+            let (origin, center) = endpoints(collider, position, fast);
+            let aabb = collider
+                .shape
+                .aabb(origin)
+                .union(&collider.shape.aabb(center));
+            (e, aabb, collider.groups)
+        }));
+
+        let mut current = BTreeMap::new();
+        for &(a, b) in broad_phase.pairs() {
+            if let (Ok((_, ca, ta, fa)), Ok((_, cb, tb, fb))) = (query.get(a), query.get(b)) {
+                let (oa, pa) = endpoints(ca, ta, fa);
+                let (ob, pb) = endpoints(cb, tb, fb);
+                let motion = (pa - oa) - (pb - ob);
+                let contact = contact(&ca.shape, pa, &cb.shape, pb).or_else(|| {
+                    if motion.length_squared() > f32::EPSILON {
+                        swept_contact(&ca.shape, oa, motion, &cb.shape, ob)
+                    } else {
+                        None
+                    }
+                });
+                if let Some(contact) = contact {
+                    current.insert((a, b), contact);
+                }
+            }
+        }
+
+        for &(a, b) in current.keys() {
+            if !collisions.0.contains_key(&(a, b)) {
+                started.send(CollisionStarted(a, b));
+            }
+        }
+        for &(a, b) in collisions.0.keys() {
+            if !current.contains_key(&(a, b)) {
+                ended.send(CollisionEnded(a, b));
+            }
+        }
+        collisions.0 = current;
+    }
+
+    fn spatial_query(
+        mut spatial_query: ResMut<SpatialQuery>,
+        query: Query<(Entity, &Collider, &Position)>,
+    ) {
+        spatial_query.update(
+            query
+                .iter()
+                .map(|(e, collider, position)| (e, *collider, collider.center(position.current))),
+        );
+    }
+
+    fn physics(mut query: Query<(Option<&Velocity>, Option<&Knockback>, &mut Position)>) {
+        let dt = TIMESTEP as f32;
+        for (velocity, knockback, mut position) in query.iter_mut() {
+            position.previous = position.current;
+            if let Some(Velocity(speed)) = velocity {
+                position.current += *speed * dt;
+            }
+            if let Some(knockback) = knockback {
+                position.current += knockback.velocity * dt;
+            }
+        }
+    }
+
+    /// Pushes moving bodies out of solids and removes the part of their velocity going into them,
+    /// so they slide along walls. Terrains blocking walkers act as solids for non flying bodies.
+    fn resolution(
+        mut bodies: QuerySet<(
+            Query<(&Collider, &Position, Option<&Terrain>), Or<(With<Solid>, With<Terrain>)>>,
+            Query<
+                (
+                    &Collider,
+                    &mut Position,
+                    &mut Velocity,
+                    Option<&mut Knockback>,
+                    Option<&Flying>,
+                ),
+                (With<Movement>, Without<Solid>),
+            >,
+        )>,
+    ) {
+        let solids: Vec<_> = bodies
+            .q0()
+            .iter()
+            .map(|(collider, position, terrain)| {
+                (
+                    *collider,
+                    collider.center(position.current),
+                    terrain.copied(),
+                )
+            })
+            .collect();
+
+        for (collider, mut position, mut velocity, mut knockback, flying) in
+            bodies.q1_mut().iter_mut()
+        {
+            for &(solid, solid_center, terrain) in solids.iter() {
+                let blocking = match terrain {
+                    Some(terrain) => terrain.blocks_walkers && flying.is_none(),
+                    None => true,
+                };
+                if !blocking || !collider.groups.interacts_with(&solid.groups) {
+                    continue;
+                }
+
+                let center = collider.center(position.current);
+                if let Some(contact) = contact(&collider.shape, center, &solid.shape, solid_center)
+                {
+                    position.current -= contact.normal * contact.depth;
+                    let into_solid = velocity.0.dot(contact.normal);
+                    if into_solid > 0.0 {
+                        velocity.0 -= into_solid * contact.normal;
+                    }
+                    if let Some(knockback) = knockback.as_mut() {
+                        let into_solid = knockback.velocity.dot(contact.normal);
+                        if into_solid > 0.0 {
+                            knockback.velocity -= into_solid * contact.normal;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// How far the time is into the next fixed step, from 0 to 1
+    fn overstep(fixed_timesteps: &FixedTimesteps) -> f32 {
+        fixed_timesteps
+            .get(TIMESTEP_LABEL)
+            .map_or(1.0, |timestep| timestep.overstep_percentage() as f32)
+    }
+
+    /// Places the rendered transforms between the two last physics states, according to how far
+    /// the time is into the next fixed step
+    fn interpolation(
+        fixed_timesteps: Res<FixedTimesteps>,
+        mut query: Query<(&Position, &mut Transform)>,
+    ) {
+        let alpha = Self::overstep(&fixed_timesteps);
+        for (position, mut transform) in query.iter_mut() {
+            let rendered = position.previous.lerp(position.current, alpha);
+            transform.translation.x = rendered.x;
+            transform.translation.y = rendered.y;
+        }
+    }
+}
+
+impl Plugin for PhysicPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        let layers = CollisionLayers::from_file("assets/collision_layers.ron")
+            .map_err(|e| {
+                println!("{}", e);
+                e
+            })
+            .unwrap_or_default();
+        app.add_resource(layers)
+            .init_resource::<BroadPhase>()
+            .init_resource::<Collisions>()
+            .init_resource::<SpatialQuery>()
+            // Bevy repeats a stage as many times as there are steps in the frame, so every
+            // step must detect, integrate and resolve before the next one starts
+            .add_stage_after(
+                stage::UPDATE,
+                STAGE,
+                Schedule::default()
+                    .with_run_criteria(FixedTimestep::step(TIMESTEP).with_label(TIMESTEP_LABEL))
+                    .with_stage(INPUT_STAGE, SystemStage::serial())
+                    .with_stage(
+                        UPDATE_STAGE,
+                        SystemStage::parallel()
+                            .with_system(Self::impulses.system())
+                            .with_system(Self::moving.system())
+                            .with_system(Self::knockback.system()),
+                    )
+                    .with_stage(
+                        PHYSICS_STAGE,
+                        SystemStage::serial()
+                            .with_system(Self::collisions.system())
+                            .with_system(Self::physics.system())
+                            .with_system(Self::resolution.system())
+                            .with_system(Self::spatial_query.system()),
+                    )
+                    .with_stage(EVENT_STAGE, SystemStage::parallel()),
+            )
+            .add_fixed_event::<CollisionStarted>()
+            .add_fixed_event::<CollisionEnded>()
+            .add_fixed_event::<Impulse>()
+            .add_stage_after(
+                STAGE,
+                INTERPOLATION_STAGE,
+                SystemStage::parallel().with_system(Self::interpolation.system()),
+            );
+    }
+}
@@ -0,0 +1,86 @@
+use bevy::prelude::*;
+
+use super::collider::{cast, contact, Collider, Shape};
+use std::cmp::Ordering;
+
+/// Hit returned by the spatial queries
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpatialHit {
+    pub entity: Entity,
+    /// Point of the hit collider touched by the query
+    pub point: Vec2,
+    /// Normal of the hit collider surface at the hit point
+    pub normal: Vec2,
+    /// Distance travelled along the query direction before the hit
+    pub distance: f32,
+}
+
+/// Snapshot of the colliders at the last physics step, used to ask questions to the physics world
+///
+/// Every query takes a layer `mask`, only colliders belonging to one of those layers are tested.
+#[derive(Default)]
+pub struct SpatialQuery {
+    colliders: Vec<(Entity, Collider, Vec2)>,
+}
+
+impl SpatialQuery {
+    pub(super) fn update(&mut self, colliders: impl Iterator<Item = (Entity, Collider, Vec2)>) {
+        self.colliders.clear();
+        self.colliders.extend(colliders);
+    }
+
+    fn filtered(&self, mask: u32) -> impl Iterator<Item = &(Entity, Collider, Vec2)> {
+        self.colliders
+            .iter()
+            .filter(move |(_, collider, _)| collider.groups.memberships & mask != 0)
+    }
+
+    pub fn raycast(
+        &self,
+        origin: Vec2,
+        direction: Vec2,
+        max_distance: f32,
+        mask: u32,
+    ) -> Option<SpatialHit> {
+        let ray = Shape::Circle { radius: 0.0 };
+        self.shape_cast(&ray, origin, direction, max_distance, mask)
+    }
+
+    /// Moves `shape` from `origin` along `direction` and returns the first collider it hits
+    pub fn shape_cast(
+        &self,
+        shape: &Shape,
+        origin: Vec2,
+        direction: Vec2,
+        max_distance: f32,
+        mask: u32,
+    ) -> Option<SpatialHit> {
+        if direction.length_squared() <= f32::EPSILON {
+            return None;
+        }
+        let motion = direction.normalize() * max_distance;
+
+        self.filtered(mask)
+            .filter_map(|&(entity, collider, center)| {
+                cast(shape, origin, motion, &collider.shape, center).map(|hit| (entity, hit))
+            })
+            .min_by(|(_, a), (_, b)| a.toi.partial_cmp(&b.toi).unwrap_or(Ordering::Equal))
+            .map(|(entity, hit)| SpatialHit {
+                entity,
+                point: origin + motion * hit.toi + shape.support(-hit.normal),
+                normal: hit.normal,
+                distance: hit.toi * max_distance,
+            })
+    }
+
+    /// Entities whose collider overlaps the given circle
+    pub fn overlap_circle(&self, center: Vec2, radius: f32, mask: u32) -> Vec<Entity> {
+        let circle = Shape::Circle { radius };
+        self.filtered(mask)
+            .filter(|(_, collider, position)| {
+                contact(&circle, center, &collider.shape, *position).is_some()
+            })
+            .map(|&(entity, _, _)| entity)
+            .collect()
+    }
+}
@@ -0,0 +1,65 @@
+use serde::{Deserialize, Serialize};
+
+/// Floor zone changing how walking bodies standing in it move
+///
+/// The coefficients multiply the ones of the body [Movement](super::Movement), and stack when
+/// several zones overlap.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Terrain {
+    pub speed: f32,
+    pub acceleration: f32,
+    pub damping: f32,
+    /// Walking bodies cannot enter the zone and are pushed out of it like from a wall
+    pub blocks_walkers: bool,
+}
+
+impl Terrain {
+    /// Slippery floor, slow to start and to stop on
+    pub fn ice() -> Self {
+        Self {
+            acceleration: 0.2,
+            damping: 0.05,
+            ..Default::default()
+        }
+    }
+
+    /// Sticky floor slowing down everything walking on it
+    pub fn creep() -> Self {
+        Self {
+            speed: 0.5,
+            ..Default::default()
+        }
+    }
+
+    pub fn pit() -> Self {
+        Self {
+            blocks_walkers: true,
+            ..Default::default()
+        }
+    }
+
+    /// Combines the effects of two overlapping zones
+    pub fn stack(self, other: Terrain) -> Self {
+        Self {
+            speed: self.speed * other.speed,
+            acceleration: self.acceleration * other.acceleration,
+            damping: self.damping * other.damping,
+            blocks_walkers: self.blocks_walkers || other.blocks_walkers,
+        }
+    }
+}
+
+impl Default for Terrain {
+    fn default() -> Self {
+        Self {
+            speed: 1.0,
+            acceleration: 1.0,
+            damping: 1.0,
+            blocks_walkers: false,
+        }
+    }
+}
+
+/// Marks a body moving above the floor, ignoring terrain effects and pits
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Flying;
@@ -1,4 +1,6 @@
 use crate::animation::*;
+use crate::attribute::MovementSpeed;
+use crate::health::Dead;
 use crate::input::*;
 use crate::physic::*;
 use crate::weapons::*;
@@ -10,8 +12,13 @@ pub struct Player;
 pub struct PlayerBundle {
     pub player: Player,
     pub weapon: TearWeapon,
+    pub position: Position,
     pub velocity: Velocity,
+    pub knockback: Knockback,
     pub movement: Movement,
+    pub collider: Collider,
+    pub actions: Actions<Action>,
+    pub device: InputDevice,
 }
 
 pub struct PlayerPlugin;
@@ -19,11 +26,10 @@ pub struct PlayerPlugin;
 impl PlayerPlugin {
     fn player_movement(
         mut animation_events: ResMut<Events<PlayerAnimEvent>>,
-        actions: Res<Actions<Action>>,
-        mut query: Query<&mut Movement, With<Player>>,
+        mut query: Query<(Entity, &Actions<Action>, &mut Movement), (With<Player>, Without<Dead>)>,
     ) {
         use Action::*;
-        for mut movement in query.iter_mut() {
+        for (player, actions, mut movement) in query.iter_mut() {
             if let Some(Move(direction)) = actions.get(Move) {
                 movement.direction = Some(*direction);
             } else {
@@ -32,19 +38,28 @@ impl PlayerPlugin {
 
             if actions.just_started(Move).is_some() {
                 animation_events.send(PlayerAnimEvent {
+                    player,
                     state: AnimState::Move(AnimOrientation::Side),
                 });
             } else if actions.just_finished(Move).is_some() {
                 animation_events.send(PlayerAnimEvent {
+                    player,
                     state: AnimState::Idle(AnimOrientation::Side),
                 });
             }
         }
     }
+
+    fn movement_speed(mut query: Query<(&MovementSpeed, &mut Movement), Changed<MovementSpeed>>) {
+        for (speed, mut movement) in query.iter_mut() {
+            movement.speed = speed.0;
+        }
+    }
 }
 
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut AppBuilder) {
-        app.add_system(Self::player_movement.system());
+        app.add_fixed_system(UPDATE_STAGE, Self::movement_speed.system())
+            .add_fixed_system(UPDATE_STAGE, Self::player_movement.system());
     }
 }
@@ -0,0 +1,165 @@
+use crate::input::{Action, InputFocus, KeyBindings};
+
+use bevy::prelude::*;
+
+/// Screen listing the actions and their keys, opened with F2
+///
+/// Up and down select an action, enter waits for the key to add to it and delete removes all
+/// its keys. A key already used by another action has to be pressed twice to be moved. Every
+/// change is saved to the bindings file.
+#[derive(Default)]
+struct RebindScreen {
+    open: bool,
+    selected: usize,
+    listening: bool,
+    conflict: Option<KeyCode>,
+    message: String,
+}
+
+struct RebindText;
+
+pub struct RebindPlugin;
+
+impl RebindPlugin {
+    const TOGGLE: KeyCode = KeyCode::F2;
+
+    fn label(action: Action) -> String {
+        let direction = |direction: Vec2| {
+            if direction.x > f32::EPSILON {
+                "right"
+            } else if direction.x < -f32::EPSILON {
+                "left"
+            } else if direction.y > f32::EPSILON {
+                "up"
+            } else {
+                "down"
+            }
+        };
+        match action {
+            Action::Move(dir) => format!("move {}", direction(dir)),
+            Action::Shoot(dir) => format!("shoot {}", direction(dir)),
+            action => format!("{:?}", action).to_lowercase(),
+        }
+    }
+
+    fn setup(commands: &mut Commands, asset_server: Res<AssetServer>) {
+        commands
+            .spawn(TextBundle {
+                style: Style {
+                    align_self: AlignSelf::FlexStart,
+                    margin: Rect::all(Val::Px(10.0)),
+                    ..Default::default()
+                },
+                text: Text {
+                    value: "".to_string(),
+                    font: asset_server.load("fonts/nova_mono.ttf"),
+                    style: TextStyle {
+                        font_size: 20.0,
+                        color: Color::WHITE,
+                        ..Default::default()
+                    },
+                },
+                ..Default::default()
+            })
+            .with(RebindText);
+    }
+
+    fn input(
+        keys: Res<Input<KeyCode>>,
+        mut screen: ResMut<RebindScreen>,
+        mut focus: ResMut<InputFocus>,
+        mut bindings: ResMut<KeyBindings>,
+    ) {
+        if keys.just_pressed(Self::TOGGLE) {
+            screen.open = !screen.open;
+            screen.listening = false;
+            screen.conflict = None;
+            screen.message.clear();
+            focus.gameplay = !screen.open;
+            return;
+        }
+        if !screen.open {
+            return;
+        }
+
+        let actions = Action::bindable();
+        let action = actions[screen.selected];
+        if screen.listening {
+            if let Some(&key) = keys.get_just_pressed().next() {
+                let result = if screen.conflict == Some(key) {
+                    bindings.rebind(key, action);
+                    Ok(())
+                } else {
+                    bindings.bind(key, action)
+                };
+                match result {
+                    Ok(()) => {
+                        screen.listening = false;
+                        screen.conflict = None;
+                        screen.message = match bindings.save(KeyBindings::PATH) {
+                            Ok(()) => format!("{:?} bound to {}", key, Self::label(action)),
+                            Err(e) => format!("Cannot save bindings: {}", e),
+                        };
+                    }
+                    Err(conflict) => {
+                        screen.conflict = Some(key);
+                        screen.message = format!(
+                            "{:?} is used by {}, press it again to move it",
+                            conflict.key,
+                            Self::label(conflict.action)
+                        );
+                    }
+                }
+            }
+        } else if keys.just_pressed(KeyCode::Up) {
+            screen.selected = (screen.selected + actions.len() - 1) % actions.len();
+        } else if keys.just_pressed(KeyCode::Down) {
+            screen.selected = (screen.selected + 1) % actions.len();
+        } else if keys.just_pressed(KeyCode::Return) {
+            screen.listening = true;
+            screen.message = format!("Press a key for {}", Self::label(action));
+        } else if keys.just_pressed(KeyCode::Delete) {
+            bindings.clear(action);
+            screen.message = match bindings.save(KeyBindings::PATH) {
+                Ok(()) => format!("Cleared {}", Self::label(action)),
+                Err(e) => format!("Cannot save bindings: {}", e),
+            };
+        }
+    }
+
+    fn display(
+        screen: Res<RebindScreen>,
+        bindings: Res<KeyBindings>,
+        mut query: Query<&mut Text, With<RebindText>>,
+    ) {
+        for mut text in query.iter_mut() {
+            text.value = if screen.open {
+                let lines: Vec<_> = Action::bindable()
+                    .iter()
+                    .enumerate()
+                    .map(|(i, &action)| {
+                        let cursor = if i == screen.selected { ">" } else { " " };
+                        let keys: Vec<_> = bindings
+                            .keys(action)
+                            .iter()
+                            .map(|key| format!("{:?}", key))
+                            .collect();
+                        format!("{} {:<12} {}", cursor, Self::label(action), keys.join(", "))
+                    })
+                    .collect();
+                format!("{}\n\n{}", lines.join("\n"), screen.message)
+            } else {
+                String::new()
+            };
+        }
+    }
+}
+
+impl Plugin for RebindPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        app.init_resource::<RebindScreen>()
+            .add_startup_system(Self::setup.system())
+            .add_system(Self::input.system())
+            .add_system(Self::display.system());
+    }
+}
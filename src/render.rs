@@ -1,7 +1,142 @@
-use bevy::prelude::*;
+use crate::animation::{TEAR_TILE, ZOOM};
+use crate::character::CharacterSprite;
+use crate::health::{HitReaction, Invincibility};
+use crate::input::Cursor;
+use crate::player::Player;
+use crate::weapons::TearTag;
+
+use bevy::{prelude::*, render::camera::Camera, render::render_graph::base::camera};
 
 pub struct Materials {
-    pub player: Handle<TextureAtlas>,
     pub tears: Handle<TextureAtlas>,
     pub ground: Handle<ColorMaterial>,
 }
+
+/// Loads the textures and gives a sprite to the entities spawned by the gameplay plugins
+///
+/// The game can run without this plugin, for instance in the headless simulation.
+pub struct RenderPlugin;
+
+impl RenderPlugin {
+    fn setup(
+        command: &mut Commands,
+        asset_server: Res<AssetServer>,
+        mut atlases: ResMut<Assets<TextureAtlas>>,
+        mut textures: ResMut<Assets<ColorMaterial>>,
+    ) {
+        asset_server.watch_for_changes().unwrap();
+
+        let tear_handle = asset_server.load("tear.png");
+        let ground_handle = asset_server.load("ground.png");
+        let tear_atlas =
+            TextureAtlas::from_grid(tear_handle, Vec2::new(TEAR_TILE, TEAR_TILE), 3, 1);
+        let materials = Materials {
+            tears: atlases.add(tear_atlas),
+            ground: textures.add(ground_handle.into()),
+        };
+
+        command
+            .spawn(Camera2dBundle::default())
+            .spawn(SpriteBundle {
+                transform: Transform::from_scale(Vec3::splat(ZOOM)),
+                material: materials.ground.clone(),
+                ..Default::default()
+            })
+            .insert_resource(materials);
+    }
+
+    fn player_sprites(
+        command: &mut Commands,
+        asset_server: Res<AssetServer>,
+        mut atlases: ResMut<Assets<TextureAtlas>>,
+        query: Query<
+            (Entity, &Transform, &CharacterSprite),
+            (With<Player>, Without<TextureAtlasSprite>),
+        >,
+    ) {
+        for (e, transform, sprite) in query.iter() {
+            let (width, height) = sprite.tile_size;
+            let atlas = TextureAtlas::from_grid(
+                asset_server.load(sprite.texture.as_str()),
+                Vec2::new(width, height),
+                sprite.columns,
+                sprite.rows,
+            );
+            command.insert(
+                e,
+                SpriteSheetBundle {
+                    texture_atlas: atlases.add(atlas),
+                    transform: *transform,
+                    ..Default::default()
+                },
+            );
+        }
+    }
+
+    fn tear_sprites(
+        command: &mut Commands,
+        materials: Res<Materials>,
+        query: Query<(Entity, &Transform), (With<TearTag>, Without<TextureAtlasSprite>)>,
+    ) {
+        for (e, transform) in query.iter() {
+            command.insert(
+                e,
+                SpriteSheetBundle {
+                    texture_atlas: materials.tears.clone(),
+                    transform: *transform,
+                    ..Default::default()
+                },
+            );
+        }
+    }
+
+    /// Converts the cursor position from the window to the world through the 2D camera
+    fn cursor(
+        mut screen: Local<Option<(WindowId, Vec2)>>,
+        mut event_reader: Local<EventReader<CursorMoved>>,
+        cursor_events: Res<Events<CursorMoved>>,
+        windows: Res<Windows>,
+        mut cursor: ResMut<Cursor>,
+        cameras: Query<(&Camera, &GlobalTransform)>,
+    ) {
+        if let Some(moved) = event_reader.latest(&cursor_events) {
+            *screen = Some((moved.id, moved.position));
+        }
+
+        cursor.0 = screen.and_then(|(id, position)| {
+            let window = windows.get(id)?;
+            let (_, transform) = cameras
+                .iter()
+                .find(|(camera, _)| camera.name.as_deref() == Some(camera::CAMERA_2D))?;
+            let size = Vec2::new(window.width(), window.height());
+            let centered = position - 0.5 * size;
+            let world = transform.compute_matrix() * centered.extend(0.0).extend(1.0);
+            Some(Vec2::new(world.x, world.y))
+        });
+    }
+
+    /// Blinks the sprites of the entities that just got hit
+    fn flash(
+        reaction: Res<HitReaction>,
+        mut query: Query<(&Invincibility, &mut TextureAtlasSprite)>,
+    ) {
+        for (invincibility, mut sprite) in query.iter_mut() {
+            sprite.color = if reaction.flashing(invincibility) {
+                Color::rgba(1.0, 1.0, 1.0, 0.2)
+            } else {
+                Color::WHITE
+            };
+        }
+    }
+}
+
+impl Plugin for RenderPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        // Sprites are added before the update so the transforms are propagated before rendering
+        app.add_startup_system(Self::setup.system())
+            .add_system_to_stage(stage::PRE_UPDATE, Self::player_sprites.system())
+            .add_system_to_stage(stage::PRE_UPDATE, Self::tear_sprites.system())
+            .add_system_to_stage(stage::PRE_UPDATE, Self::cursor.system())
+            .add_system(Self::flash.system());
+    }
+}
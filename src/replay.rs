@@ -0,0 +1,230 @@
+//! Recording of the players' actions, and their replay
+//!
+//! `--record <file>` saves the actions of every player at each fixed step, along with the seed
+//! of the run and the character. `--replay <file>` plays them back instead of reading the input
+//! devices.
+//!
+//! The file starts with a line for the seed and the character, followed by a line per step,
+//! written as the game runs so a crash does not lose the recording.
+use crate::arg_value;
+use crate::character::SelectedCharacter;
+use crate::input::{Action, Actions, InputDevice, InputSource, JoinEvent};
+use crate::physic::{self, FixedStep};
+use crate::rng::GameRng;
+
+use bevy::prelude::*;
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::File,
+    io::{LineWriter, Write},
+};
+
+/// Serializable [InputDevice](InputDevice)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+enum RecordedDevice {
+    Keyboard,
+    Gamepad(usize),
+}
+
+impl From<InputDevice> for RecordedDevice {
+    fn from(device: InputDevice) -> Self {
+        match device {
+            InputDevice::Keyboard => RecordedDevice::Keyboard,
+            InputDevice::Gamepad(Gamepad(id)) => RecordedDevice::Gamepad(id),
+        }
+    }
+}
+
+impl From<RecordedDevice> for InputDevice {
+    fn from(device: RecordedDevice) -> Self {
+        match device {
+            RecordedDevice::Keyboard => InputDevice::Keyboard,
+            RecordedDevice::Gamepad(id) => InputDevice::Gamepad(Gamepad(id)),
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Tick {
+    /// Active actions of each player
+    players: Vec<(RecordedDevice, Vec<Action>)>,
+    /// Devices that joined the game
+    #[serde(default)]
+    joins: Vec<RecordedDevice>,
+}
+
+/// First line of a recording
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Header {
+    seed: u64,
+    character: String,
+}
+
+#[derive(Debug, Default)]
+struct Recording {
+    header: Header,
+    /// Ticks of every fixed step, by index
+    ticks: Vec<Tick>,
+}
+
+impl Recording {
+    fn load(path: &str) -> Result<Self, ron::Error> {
+        let file = std::fs::read_to_string(path)?;
+        let mut lines = file.lines().filter(|line| !line.trim().is_empty());
+        let header = ron::from_str(lines.next().unwrap_or_default())?;
+        let ticks = lines.map(ron::from_str).collect::<Result<_, _>>()?;
+        Ok(Self { header, ticks })
+    }
+}
+
+/// Writes a line per step, which the line writer flushes right away
+struct Recorder {
+    path: String,
+    file: Option<LineWriter<File>>,
+}
+
+impl Recorder {
+    fn create(path: String, header: &Header) -> std::io::Result<Self> {
+        let mut file = LineWriter::new(File::create(&path)?);
+        Self::write_line(&mut file, header)?;
+        Ok(Self {
+            path,
+            file: Some(file),
+        })
+    }
+
+    fn write_line<T: Serialize>(file: &mut LineWriter<File>, value: &T) -> std::io::Result<()> {
+        let ron = ron::to_string(value)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e.to_string()))?;
+        writeln!(file, "{}", ron)
+    }
+}
+
+struct Replayer {
+    recording: Recording,
+    /// Index of the next step to play
+    tick: usize,
+}
+
+pub struct ReplayPlugin;
+
+impl ReplayPlugin {
+    fn same_kind(a: &Action, b: &Action) -> bool {
+        std::mem::discriminant(a) == std::mem::discriminant(b)
+    }
+
+    fn record(
+        mut recorder: ResMut<Recorder>,
+        mut event_reader: Local<EventReader<JoinEvent>>,
+        join_events: Res<Events<JoinEvent>>,
+        query: Query<(&InputDevice, &Actions<Action>)>,
+    ) {
+        let players = query
+            .iter()
+            .map(|(&device, actions)| (device.into(), actions.active().copied().collect()))
+            .collect();
+        let joins = event_reader
+            .iter(&join_events)
+            .map(|join| join.device.into())
+            .collect();
+        let tick = Tick { players, joins };
+
+        let written = match recorder.file.as_mut() {
+            Some(file) => Recorder::write_line(file, &tick),
+            None => return,
+        };
+        if let Err(e) = written {
+            println!("Cannot write recording to {}: {}", recorder.path, e);
+            recorder.file = None;
+        }
+    }
+
+    fn replay(
+        mut replayer: ResMut<Replayer>,
+        mut join_events: ResMut<Events<JoinEvent>>,
+        mut query: Query<(&InputDevice, &mut Actions<Action>)>,
+    ) {
+        let index = replayer.tick;
+        replayer.tick += 1;
+        let tick = match replayer.recording.ticks.get(index) {
+            Some(tick) => tick,
+            None => {
+                if index == replayer.recording.ticks.len() {
+                    info!("Replay finished");
+                }
+                for (_, mut actions) in query.iter_mut() {
+                    actions.update();
+                    let active: Vec<_> = actions.active().copied().collect();
+                    active.into_iter().for_each(|action| actions.stop(action));
+                }
+                return;
+            }
+        };
+
+        for (&device, mut actions) in query.iter_mut() {
+            let device = RecordedDevice::from(device);
+            let recorded = tick
+                .players
+                .iter()
+                .find(|&&(recorded, _)| recorded == device)
+                .map(|(_, actions)| actions.as_slice())
+                .unwrap_or_default();
+
+            actions.update();
+            let stopped: Vec<_> = actions
+                .active()
+                .copied()
+                .filter(|active| {
+                    recorded
+                        .iter()
+                        .all(|action| !Self::same_kind(action, active))
+                })
+                .collect();
+            stopped.into_iter().for_each(|action| actions.stop(action));
+            recorded.iter().for_each(|&action| actions.start(action));
+        }
+
+        for &device in tick.joins.iter() {
+            join_events.send(JoinEvent {
+                device: device.into(),
+            });
+        }
+    }
+}
+
+impl Plugin for ReplayPlugin {
+    // Both systems run in the fixed steps, after the input plugin resolved the actions of the
+    // devices, so the recording has the actions each step played with
+    fn build(&self, app: &mut AppBuilder) {
+        if let Some(path) = arg_value("--replay") {
+            match Recording::load(&path) {
+                Ok(recording) => {
+                    app.add_resource(GameRng::new(recording.header.seed))
+                        .add_resource(SelectedCharacter(recording.header.character.clone()))
+                        .add_resource(InputSource::External)
+                        .add_resource(Replayer { recording, tick: 0 })
+                        .add_fixed_system(physic::INPUT_STAGE, Self::replay.system());
+                }
+                Err(e) => println!("Cannot load replay {}: {}", path, e),
+            }
+        } else if let Some(path) = arg_value("--record") {
+            let header = Header {
+                seed: app.resources().get::<GameRng>().map_or(0, |rng| rng.seed()),
+                character: app
+                    .resources()
+                    .get::<SelectedCharacter>()
+                    .map(|character| character.0.clone())
+                    .unwrap_or_default(),
+            };
+            match Recorder::create(path.clone(), &header) {
+                Ok(recorder) => {
+                    info!("Recording to {}", path);
+                    app.add_resource(recorder)
+                        .add_fixed_system(physic::INPUT_STAGE, Self::record.system());
+                }
+                Err(e) => println!("Cannot record to {}: {}", path, e),
+            }
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use crate::arg_value;
+use crate::attribute::Luck;
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Random number generator of the game
+///
+/// Every random roll of the gameplay goes through this resource, so a run can be reproduced
+/// from its seed.
+#[derive(Debug, Clone)]
+pub struct GameRng {
+    seed: u64,
+    state: u64,
+}
+
+impl GameRng {
+    pub fn new(seed: u64) -> Self {
+        Self { seed, state: seed }
+    }
+
+    /// Reads the `--seed <number>` command line option, or seeds from the clock
+    pub fn from_args() -> Self {
+        let seed = arg_value("--seed")
+            .and_then(|seed| seed.parse().ok())
+            .unwrap_or_else(|| {
+                SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|time| time.as_nanos() as u64)
+                    .unwrap_or_default()
+            });
+        Self::new(seed)
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// SplitMix64
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform number in `[0, 1)`
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    pub fn roll(&mut self, probability: f32) -> bool {
+        self.next_f32() < probability
+    }
+}
+
+/// Luck-scaled probability of an effect, as written in item data
+///
+/// The probability is `base + luck * per_luck`, clamped between 0 and `cap`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Chance {
+    pub base: f32,
+    #[serde(default)]
+    pub per_luck: f32,
+    #[serde(default = "Chance::no_cap")]
+    pub cap: f32,
+}
+
+impl Chance {
+    fn no_cap() -> f32 {
+        1.0
+    }
+
+    pub fn probability(&self, luck: Luck) -> f32 {
+        (self.base + luck.0 * self.per_luck).min(self.cap).max(0.0)
+    }
+
+    pub fn roll(&self, luck: Luck, rng: &mut GameRng) -> bool {
+        rng.roll(self.probability(luck))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chance_scales_with_luck_up_to_cap() {
+        let chance = Chance {
+            base: 0.1,
+            per_luck: 0.05,
+            cap: 0.5,
+        };
+        assert!((chance.probability(Luck(2.0)) - 0.2).abs() < 1e-6);
+        assert_eq!(chance.probability(Luck(20.0)), 0.5);
+        assert_eq!(chance.probability(Luck(-5.0)), 0.0);
+
+        let (mut a, mut b) = (GameRng::new(42), GameRng::new(42));
+        let rolls: Vec<_> = (0..100).map(|_| chance.roll(Luck(2.0), &mut a)).collect();
+        assert!(rolls
+            .iter()
+            .all(|&roll| roll == chance.roll(Luck(2.0), &mut b)));
+        assert!(rolls.contains(&true) && rolls.contains(&false));
+    }
+}
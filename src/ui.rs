@@ -1,3 +1,5 @@
+use crate::physic::{BroadPhase, PhysicDebug};
+
 use bevy::diagnostic::{Diagnostics, FrameTimeDiagnosticsPlugin};
 use bevy::prelude::*;
 
@@ -36,6 +38,8 @@ impl UIPlugin {
         mut timer: Local<Timer>,
         time: Res<Time>,
         diagnostics: Res<Diagnostics>,
+        physic_debug: Res<PhysicDebug>,
+        broad_phase: Res<BroadPhase>,
         mut query: Query<&mut Text, With<FpsText>>,
     ) {
         timer.set_duration(1.0);
@@ -46,7 +50,10 @@ impl UIPlugin {
                     .get(FrameTimeDiagnosticsPlugin::FPS)
                     .and_then(|diag| diag.average())
                 {
-                    text.value = format!("fps: {:.0}", fps);
+                    text.value = match physic_debug.stats(&broad_phase) {
+                        Some(stats) => format!("fps: {:.0}\n{}", fps, stats),
+                        None => format!("fps: {:.0}", fps),
+                    };
                 }
             }
         }
@@ -1,34 +1,55 @@
 use crate::animation::*;
+use crate::attribute::{AttackRate, Luck, ProjectileSpeed, Range, ShotHeight, TearSize};
+use crate::health::Dead;
 use crate::input::*;
 use crate::physic::*;
 use crate::player::*;
+use crate::rng::{Chance, GameRng};
 use crate::weapons::*;
 
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
 #[derive(Bundle)]
 pub struct TearProjectileBundle {
+    pub position: Position,
     pub velocity: Velocity,
-    pub lifetime: Timer,
+    pub flight: TearFlight,
     pub tag: TearTag,
+    pub shooter: Shooter,
+    pub collider: Collider,
+    pub fast: FastProjectile,
 }
 
+/// Effect a character's tears get when its chance, scaled by the player's luck, rolls
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum TearEffect {
+    /// The tear is shot bigger, which also grows its collider
+    Large { chance: Chance, scale: f32 },
+}
+
+/// Tear effects of a player, rolled for every tear it shoots
+#[derive(Debug, Clone, Default)]
+pub struct TearEffects(pub Vec<TearEffect>);
+
 #[derive(Default)]
 pub struct TearWeapon {
     cooldown: Timer,
     speed: f32,
-    lifetime: f32,
+    distance: f32,
+    size: f32,
 }
 
 impl TearWeapon {
-    pub fn new(cooldown: f32, speed: f32, lifetime: f32) -> Self {
+    pub fn new(cooldown: f32, speed: f32, distance: f32, size: f32) -> Self {
         let mut timer = Timer::from_seconds(cooldown, false);
         timer.tick(cooldown);
 
         Self {
             cooldown: timer,
             speed,
-            lifetime,
+            distance,
+            size,
         }
     }
 
@@ -44,17 +65,69 @@ impl TearWeapon {
         self.cooldown.tick(dt);
     }
 
+    pub fn set_cooldown(&mut self, cooldown: f32) {
+        self.cooldown.set_duration(cooldown);
+    }
+
+    pub fn set_speed(&mut self, speed: f32) {
+        self.speed = speed;
+    }
+
+    pub fn set_distance(&mut self, distance: f32) {
+        self.distance = distance;
+    }
+
+    pub fn set_size(&mut self, size: f32) {
+        self.size = size;
+    }
+
+    /// Keeps the weapon in sync with the attributes of its player
+    fn attributes(
+        mut query: Query<
+            (
+                &mut TearWeapon,
+                &AttackRate,
+                &Range,
+                &ShotHeight,
+                &ProjectileSpeed,
+                &TearSize,
+            ),
+            Or<(
+                Changed<AttackRate>,
+                Changed<Range>,
+                Changed<ShotHeight>,
+                Changed<ProjectileSpeed>,
+                Changed<TearSize>,
+            )>,
+        >,
+    ) {
+        for (mut weapon, attack_rate, range, height, projectile_speed, size) in query.iter_mut() {
+            weapon.set_cooldown(attack_rate.cooldown());
+            weapon.set_distance(range.distance(*height));
+            weapon.set_speed(projectile_speed.0);
+            weapon.set_size(size.0);
+        }
+    }
+
     fn update(
-        time: Res<Time>,
-        actions: Res<Actions<Action>>,
+        mut rng: ResMut<GameRng>,
         mut shoot_events: ResMut<Events<SpawnProjectileEvent>>,
         mut animation_events: ResMut<Events<PlayerAnimEvent>>,
-        mut query: Query<(Entity, &mut TearWeapon), With<Player>>,
+        mut query: Query<
+            (
+                Entity,
+                &Actions<Action>,
+                &mut TearWeapon,
+                &Luck,
+                &TearEffects,
+            ),
+            (With<Player>, Without<Dead>),
+        >,
     ) {
         use Action::*;
 
-        for (e, mut weapon) in query.iter_mut() {
-            weapon.tick(time.delta_seconds());
+        for (e, actions, mut weapon, &luck, effects) in query.iter_mut() {
+            weapon.tick(TIMESTEP as f32);
 
             if !weapon.available() {
                 continue;
@@ -62,13 +135,25 @@ impl TearWeapon {
 
             if let Some(Shoot(direction)) = actions.get(Shoot) {
                 weapon.reset();
+                let mut size = weapon.size;
+                for effect in &effects.0 {
+                    match *effect {
+                        TearEffect::Large { chance, scale } => {
+                            if chance.roll(luck, &mut rng) {
+                                size *= scale;
+                            }
+                        }
+                    }
+                }
                 shoot_events.send(SpawnProjectileEvent {
                     parent: e,
                     direction: direction.normalize(),
                     speed: weapon.speed,
-                    lifetime: weapon.lifetime,
+                    distance: weapon.distance,
+                    size,
                 });
                 animation_events.send(PlayerAnimEvent {
+                    player: e,
                     state: AnimState::Attack(AnimOrientation::Side),
                 });
             }
@@ -78,6 +163,7 @@ impl TearWeapon {
 
 impl Plugin for TearWeapon {
     fn build(&self, app: &mut AppBuilder) {
-        app.add_system(Self::update.system());
+        app.add_fixed_system(UPDATE_STAGE, Self::attributes.system())
+            .add_fixed_system(UPDATE_STAGE, Self::update.system());
     }
 }
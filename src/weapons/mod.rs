@@ -2,7 +2,6 @@ mod default;
 
 use crate::animation::*;
 use crate::physic::*;
-use crate::render::Materials;
 
 use bevy::{app::PluginGroupBuilder, prelude::*};
 
@@ -11,11 +10,38 @@ pub use default::*;
 #[derive(Debug, Default, Copy, Clone)]
 pub struct TearTag;
 
+/// Distance a tear has flown, it drops once it has travelled its whole flight distance
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TearFlight {
+    pub distance: f32,
+    pub travelled: f32,
+}
+
+impl TearFlight {
+    pub fn new(distance: f32) -> Self {
+        Self {
+            distance,
+            travelled: 0.0,
+        }
+    }
+
+    pub fn dropped(&self) -> bool {
+        self.travelled >= self.distance
+    }
+}
+
+/// Entity that shot a tear
+#[derive(Debug, Clone, Copy)]
+pub struct Shooter(pub Entity);
+
 pub struct SpawnProjectileEvent {
     pub parent: Entity,
     pub direction: Vec2,
     pub speed: f32,
-    pub lifetime: f32,
+    /// Distance the tear flies before dropping
+    pub distance: f32,
+    /// Scale of the tear sprite and collider
+    pub size: f32,
 }
 
 pub struct DespawnProjectileEvent(pub Entity);
@@ -23,51 +49,65 @@ pub struct DespawnProjectileEvent(pub Entity);
 pub struct WeaponPlugins;
 
 impl WeaponPlugins {
+    fn flight(mut query: Query<(&Velocity, &mut TearFlight)>) {
+        let dt = TIMESTEP as f32;
+        for (velocity, mut flight) in query.iter_mut() {
+            flight.travelled += velocity.0.length() * dt;
+        }
+    }
+
     fn update_projectile(
-        time: Res<Time>,
         mut projectile_events: ResMut<Events<DespawnProjectileEvent>>,
-        mut query: Query<(Entity, &mut Timer), With<TearTag>>,
+        query: Query<(Entity, &TearFlight), With<TearTag>>,
     ) {
         let events: Vec<_> = query
-            .iter_mut()
-            .map(|(e, mut timer)| {
-                timer.tick(time.delta_seconds());
-                (e, timer)
-            })
-            .filter(|(_, timer)| timer.finished())
+            .iter()
+            .filter(|(_, flight)| flight.dropped())
             .map(|(entity, _)| DespawnProjectileEvent(entity))
             .collect();
         projectile_events.extend(events.into_iter());
     }
 
+    fn hit_solid(
+        mut event_reader: Local<EventReader<CollisionStarted>>,
+        collision_events: Res<Events<CollisionStarted>>,
+        mut projectile_events: ResMut<Events<DespawnProjectileEvent>>,
+        tears: Query<&TearTag>,
+        solids: Query<&Solid>,
+    ) {
+        for &CollisionStarted(a, b) in event_reader.iter(&collision_events) {
+            for &(tear, other) in [(a, b), (b, a)].iter() {
+                if tears.get(tear).is_ok() && solids.get(other).is_ok() {
+                    projectile_events.send(DespawnProjectileEvent(tear));
+                }
+            }
+        }
+    }
+
     fn spawn(
         command: &mut Commands,
-        materials: Res<Materials>,
+        layers: Res<CollisionLayers>,
         projectile_events: Res<Events<SpawnProjectileEvent>>,
         mut event_reader: Local<EventReader<SpawnProjectileEvent>>,
-        query: Query<(&Transform, &Velocity)>,
+        query: Query<(&Position, &Transform, &Velocity)>,
     ) {
         for shoot in event_reader.iter(&projectile_events) {
-            let direction = if shoot.direction.x.abs() > f32::EPSILON {
-                Vec2::new(shoot.direction.x.signum(), 0.0)
-            } else {
-                Vec2::new(0.0, shoot.direction.y.signum())
-            };
-
-            if let Ok((transform, velocity)) = query.get(shoot.parent) {
+            if let Ok((position, transform, velocity)) = query.get(shoot.parent) {
+                let collider = Collider::circle(TEAR_RADIUS * shoot.size)
+                    .with_groups(layers.profile("player_tear"));
                 command
                     .spawn(TearProjectileBundle {
-                        velocity: Velocity(direction * shoot.speed + 0.33 * velocity.0),
-                        lifetime: Timer::from_seconds(shoot.lifetime, false),
+                        position: Position::new(position.current),
+                        velocity: Velocity(shoot.direction * shoot.speed + 0.33 * velocity.0),
+                        flight: TearFlight::new(shoot.distance),
                         tag: TearTag,
+                        shooter: Shooter(shoot.parent),
+                        collider,
+                        fast: FastProjectile::default(),
                     })
-                    .with_bundle(SpriteSheetBundle {
-                        transform: Transform {
-                            translation: transform.translation,
-                            scale: Vec3::splat(ZOOM),
-                            ..Default::default()
-                        },
-                        texture_atlas: materials.tears.clone(),
+                    .with(Transform {
+                        translation: transform.translation,
+                        scale: Vec3::splat(ZOOM * shoot.size),
                         ..Default::default()
                     })
                     .with_bundle(AnimationBundle {
@@ -91,11 +131,13 @@ impl WeaponPlugins {
 
 impl Plugin for WeaponPlugins {
     fn build(&self, app: &mut AppBuilder) {
-        app.add_event::<SpawnProjectileEvent>()
-            .add_event::<DespawnProjectileEvent>()
-            .add_system(Self::update_projectile.system())
-            .add_system(Self::spawn.system())
-            .add_system(Self::despawn.system());
+        app.add_fixed_event::<SpawnProjectileEvent>()
+            .add_fixed_event::<DespawnProjectileEvent>()
+            .add_fixed_system(UPDATE_STAGE, Self::flight.system())
+            .add_fixed_system(UPDATE_STAGE, Self::update_projectile.system())
+            .add_fixed_system(UPDATE_STAGE, Self::hit_solid.system())
+            .add_fixed_system(UPDATE_STAGE, Self::spawn.system())
+            .add_fixed_system(UPDATE_STAGE, Self::despawn.system());
     }
 }
 